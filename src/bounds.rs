@@ -0,0 +1,54 @@
+use bevy::{camera::primitives::Aabb, prelude::*};
+
+/// World-space box around every mesh of a spawned model
+#[derive(Clone, Copy, Debug)]
+pub struct ModelBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl ModelBounds {
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Length of the diagonal, a rough measure of the model's size
+    pub fn size(&self) -> f32 {
+        (self.max - self.min).length()
+    }
+}
+
+/// Bounds of the meshes below `root`, or `None` before they have their [`Aabb`]s
+pub fn model_bounds(
+    root: Entity,
+    children: &Query<&Children>,
+    aabbs: &Query<(&Aabb, &GlobalTransform)>,
+) -> Option<ModelBounds> {
+    let mut bounds: Option<ModelBounds> = None;
+    for (aabb, transform) in aabbs.iter_many(children.iter_descendants(root)) {
+        let (min, max) = (Vec3::from(aabb.min()), Vec3::from(aabb.max()));
+        for corner in [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ] {
+            let point = transform.transform_point(corner);
+            bounds = Some(match bounds {
+                Some(b) => ModelBounds {
+                    min: b.min.min(point),
+                    max: b.max.max(point),
+                },
+                None => ModelBounds {
+                    min: point,
+                    max: point,
+                },
+            });
+        }
+    }
+    bounds
+}
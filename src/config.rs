@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use directories::ProjectDirs;
+use serde::{Serialize, de::DeserializeOwned};
+use std::{fs, path::PathBuf};
+
+/// Per-user configuration directory, e.g. `~/.config/view3d` on Linux
+pub fn config_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "AdventureRealmGames", "view3d").map(|d| d.config_dir().to_path_buf())
+}
+
+/// Reads a JSON file from the config dir. Returns `None` if it is missing or unreadable.
+pub fn load_json<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = config_dir()?.join(file_name);
+    let contents = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Ignoring invalid config file '{}': {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Writes a value as pretty JSON into the config dir, creating the dir if needed. `file_name`
+/// may include a subfolder.
+pub fn save_json<T: Serialize>(file_name: &str, value: &T) -> Result<(), String> {
+    let path = config_dir()
+        .ok_or("No config directory available")?
+        .join(file_name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
@@ -0,0 +1,83 @@
+use bevy_egui::egui;
+use std::path::{Path, PathBuf};
+
+use crate::{files::is_model_file, selection::Selection};
+
+/// Action picked from the context menu of a file or directory
+pub enum EntryAction {
+    Open(PathBuf),
+    Rename(PathBuf),
+    Duplicate(Vec<PathBuf>),
+    /// Asks for a destination folder first
+    Move(Vec<PathBuf>),
+    CopyPath(Vec<PathBuf>),
+    Reveal(PathBuf),
+    RegenerateThumbnails(Vec<PathBuf>),
+    /// Asks for confirmation first
+    Delete(Vec<PathBuf>),
+}
+
+/// Shows the context menu of the entry at `path` on right click. Actions apply to the whole
+/// [`Selection`] when it includes `path`, otherwise to `path` alone.
+pub fn entry_context_menu(
+    response: &egui::Response,
+    path: &Path,
+    selection: &Selection,
+) -> Option<EntryAction> {
+    let mut action = None;
+
+    response.context_menu(|ui| {
+        let targets = selection.targets(path);
+        let many = targets.len() > 1;
+        if many {
+            ui.label(format!("{} items", targets.len()));
+            ui.separator();
+        }
+
+        if ui.button("Open").clicked() {
+            action = Some(EntryAction::Open(path.to_path_buf()));
+        }
+        if ui
+            .add_enabled(!many, egui::Button::new("Rename…"))
+            .clicked()
+        {
+            action = Some(EntryAction::Rename(path.to_path_buf()));
+        }
+        if ui.button("Duplicate").clicked() {
+            action = Some(EntryAction::Duplicate(targets.clone()));
+        }
+        if ui.button("Move to…").clicked() {
+            action = Some(EntryAction::Move(targets.clone()));
+        }
+        let copy_label = if many { "Copy paths" } else { "Copy path" };
+        if ui.button(copy_label).clicked() {
+            action = Some(EntryAction::CopyPath(targets.clone()));
+        }
+        if ui.button("Reveal in file manager").clicked() {
+            action = Some(EntryAction::Reveal(path.to_path_buf()));
+        }
+
+        let models: Vec<PathBuf> = targets
+            .iter()
+            .filter(|p| is_model_file(p))
+            .cloned()
+            .collect();
+        if ui
+            .add_enabled(!models.is_empty(), egui::Button::new("Regenerate thumbnail"))
+            .clicked()
+        {
+            action = Some(EntryAction::RegenerateThumbnails(models));
+        }
+
+        ui.separator();
+        if ui.button("Delete…").clicked() {
+            action = Some(EntryAction::Delete(targets));
+        }
+
+        if action.is_some() {
+            ui.close();
+        }
+    });
+
+    action
+}
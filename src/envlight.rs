@@ -2,11 +2,23 @@
 
 use bevy::{
     asset::RenderAssetUsages,
+    core_pipeline::Skybox,
+    image::{CompressedImageFormats, ImageSampler, ImageType},
     prelude::*,
     render::render_resource::{
         Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
     },
+    tasks::{AsyncComputeTaskPool, Task, block_on, poll_once},
 };
+use bevy_egui::egui;
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    path::{Path, PathBuf},
+};
+
+use crate::{lighting::Lighting, ui::DialogResponse};
 
 // pub(super) fn plugin(app: &mut App) {
 //     let _ = app;
@@ -79,3 +91,576 @@ fn hemispherical_gradient_cubemap(
         )
     }
 }
+
+/// Edge length of the sharpest specular cubemap face
+const SPECULAR_SIZE: u32 = 256;
+/// Edge length of the diffuse cubemap faces; irradiance has no fine detail
+const DIFFUSE_SIZE: u32 = 32;
+/// GGX samples per texel when prefiltering the rougher specular mips
+const SPECULAR_SAMPLES: u32 = 48;
+
+/// Equirectangular image as linear RGB, with a pyramid of half-size copies for blurrier lookups
+struct Equirect {
+    levels: Vec<EquirectLevel>,
+}
+
+struct EquirectLevel {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>,
+}
+
+impl EquirectLevel {
+    fn texel(&self, x: usize, y: usize) -> Vec3 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Half-size copy, averaging 2×2 blocks
+    fn downsample(&self) -> Self {
+        let (width, height) = ((self.width / 2).max(1), (self.height / 2).max(1));
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (x0, y0) = (x * 2, y * 2);
+                let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                let sum = self.texel(x0, y0)
+                    + self.texel(x1, y0)
+                    + self.texel(x0, y1)
+                    + self.texel(x1, y1);
+                pixels.push(sum * 0.25);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Bilinear lookup in the direction `dir`, wrapping around horizontally
+    fn sample(&self, dir: Vec3) -> Vec3 {
+        let u = 0.5 + dir.x.atan2(-dir.z) / TAU;
+        let v = dir.y.clamp(-1.0, 1.0).acos() / PI;
+        let x = u * self.width as f32 - 0.5;
+        let y = (v * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let wrap = |x: f32| x.rem_euclid(self.width as f32) as usize % self.width;
+        let (xa, xb) = (wrap(x0), wrap(x0 + 1.0));
+        let (ya, yb) = (y0 as usize, (y0 as usize + 1).min(self.height - 1));
+        let top = self.texel(xa, ya).lerp(self.texel(xb, ya), fx);
+        let bottom = self.texel(xa, yb).lerp(self.texel(xb, yb), fx);
+        top.lerp(bottom, fy)
+    }
+
+    /// World direction through the centre of texel (`x`, `y`)
+    fn direction(&self, x: usize, y: usize) -> Vec3 {
+        let phi = ((x as f32 + 0.5) / self.width as f32 - 0.5) * TAU;
+        let theta = (y as f32 + 0.5) / self.height as f32 * PI;
+        Vec3::new(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        )
+    }
+}
+
+impl Equirect {
+    fn from_image(image: &Image) -> Result<Self, String> {
+        let size = image.size();
+        let (width, height) = (size.x as usize, size.y as usize);
+        if width == 0 || height == 0 {
+            return Err("The image is empty".to_string());
+        }
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height as u32 {
+            for x in 0..width as u32 {
+                let color = image
+                    .get_color_at(x, y)
+                    .map_err(|e| format!("Unsupported pixel format: {e}"))?;
+                let linear = color.to_linear();
+                // Some files contain NaNs or negative values, which would spread through the blur
+                let rgb = Vec3::new(linear.red, linear.green, linear.blue);
+                pixels.push(if rgb.is_finite() {
+                    rgb.max(Vec3::ZERO)
+                } else {
+                    Vec3::ZERO
+                });
+            }
+        }
+
+        let mut levels = vec![EquirectLevel {
+            width,
+            height,
+            pixels,
+        }];
+        while levels.last().is_some_and(|level| level.width > 4) {
+            let next = levels.last().map(EquirectLevel::downsample);
+            levels.extend(next);
+        }
+        Ok(Self { levels })
+    }
+
+    /// Pyramid level whose texels are about as wide as `texel_angle` radians
+    fn level_for(&self, texel_angle: f32) -> &EquirectLevel {
+        let base_angle = TAU / self.levels[0].width as f32;
+        let level = (texel_angle / base_angle).log2().max(0.0) as usize;
+        &self.levels[level.min(self.levels.len() - 1)]
+    }
+
+    /// Lookup at a fractional pyramid level, for lobes covering `solid_angle` steradians
+    fn sample_lod(&self, dir: Vec3, solid_angle: f32) -> Vec3 {
+        let base = &self.levels[0];
+        let texel_solid_angle = TAU * PI / (base.width * base.height) as f32;
+        let lod = (0.5 * (solid_angle / texel_solid_angle).log2() + 1.0).max(0.0);
+        let level = (lod as usize).min(self.levels.len() - 1);
+        self.levels[level].sample(dir)
+    }
+}
+
+/// World direction through texel (`x`, `y`) of cubemap `face`, in the order +X, -X, +Y, -Y, +Z,
+/// -Z. Cubemaps are left-handed, so Bevy looks them up with z negated.
+fn cube_direction(face: u32, x: u32, y: u32, size: u32) -> Vec3 {
+    let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let dir = match face {
+        0 => Vec3::new(1.0, -v, -u),
+        1 => Vec3::new(-1.0, -v, u),
+        2 => Vec3::new(u, 1.0, v),
+        3 => Vec3::new(u, -1.0, -v),
+        4 => Vec3::new(u, -v, 1.0),
+        _ => Vec3::new(-u, -v, -1.0),
+    };
+    let dir = dir.normalize();
+    Vec3::new(dir.x, dir.y, -dir.z)
+}
+
+/// Packs a linear colour into the shared-exponent `Rgb9e5Ufloat` format
+fn rgb9e5(color: Vec3) -> u32 {
+    const MANTISSA_BITS: i32 = 9;
+    const BIAS: i32 = 15;
+    const MAX: f32 = 65408.0;
+
+    let color = color.clamp(Vec3::ZERO, Vec3::splat(MAX));
+    let max = color.max_element();
+    let mut exponent = if max > 0.0 {
+        (max.log2().floor() as i32).max(-BIAS - 1) + 1 + BIAS
+    } else {
+        0
+    };
+    let mut scale = 2f32.powi(exponent - BIAS - MANTISSA_BITS);
+    if (max / scale + 0.5).floor() as u32 == 1 << MANTISSA_BITS {
+        exponent += 1;
+        scale *= 2.0;
+    }
+    let [r, g, b] = (color / scale + 0.5).floor().to_array().map(|c| c as u32);
+    r | (g << 9) | (b << 18) | ((exponent as u32) << 27)
+}
+
+/// Cubemap image with `mips[level][face]` texels of `size`, `size / 2`, ... per face
+fn cubemap(size: u32, mips: Vec<[Vec<Vec3>; 6]>) -> Image {
+    let mut image = Image::new_uninit(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 6,
+        },
+        TextureDimension::D2,
+        TextureFormat::Rgb9e5Ufloat,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.texture_descriptor.mip_level_count = mips.len() as u32;
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..Default::default()
+    });
+    // Layer by layer, each with all its mips
+    let mut data = Vec::new();
+    for face in 0..6 {
+        for level in &mips {
+            data.extend(level[face].iter().flat_map(|c| rgb9e5(*c).to_le_bytes()));
+        }
+    }
+    image.data = Some(data);
+    image
+}
+
+/// Fills the six faces of a cubemap level with `texel(direction)`
+fn cube_faces(size: u32, mut texel: impl FnMut(Vec3) -> Vec3) -> [Vec<Vec3>; 6] {
+    std::array::from_fn(|face| {
+        let mut pixels = Vec::with_capacity((size * size) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                pixels.push(texel(cube_direction(face as u32, x, y, size)));
+            }
+        }
+        pixels
+    })
+}
+
+fn hammersley(i: u32, count: u32) -> Vec2 {
+    Vec2::new(
+        i as f32 / count as f32,
+        i.reverse_bits() as f32 * 2.328_306_4e-10,
+    )
+}
+
+/// Specular cubemap with one mip per roughness step, prefiltered with the GGX lobe. Bevy picks
+/// the mip as perceptual roughness × (mip count − 1).
+fn specular_cubemap(equirect: &Equirect) -> Image {
+    let mip_count = SPECULAR_SIZE.ilog2() + 1;
+    let mips = (0..mip_count)
+        .map(|mip| {
+            let size = SPECULAR_SIZE >> mip;
+            let roughness = mip as f32 / (mip_count - 1) as f32;
+            if mip == 0 {
+                // Mirror-like: a plain lookup at matching resolution
+                let level = equirect.level_for(FRAC_PI_2 / size as f32);
+                return cube_faces(size, |dir| level.sample(dir));
+            }
+            let alpha = roughness * roughness;
+            let alpha2 = alpha * alpha;
+            cube_faces(size, |normal| {
+                // The view and reflection directions are taken to equal the normal
+                let up = if normal.y.abs() < 0.999 {
+                    Vec3::Y
+                } else {
+                    Vec3::X
+                };
+                let tangent = up.cross(normal).normalize();
+                let bitangent = normal.cross(tangent);
+                let (mut sum, mut weight) = (Vec3::ZERO, 0.0);
+                for i in 0..SPECULAR_SAMPLES {
+                    let xi = hammersley(i, SPECULAR_SAMPLES);
+                    let cos_theta = ((1.0 - xi.y) / (1.0 + (alpha2 - 1.0) * xi.y)).sqrt();
+                    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                    let phi = TAU * xi.x;
+                    let half = tangent * (sin_theta * phi.cos())
+                        + bitangent * (sin_theta * phi.sin())
+                        + normal * cos_theta;
+                    let light = half * 2.0 * normal.dot(half) - normal;
+                    let n_dot_l = normal.dot(light);
+                    if n_dot_l <= 0.0 {
+                        continue;
+                    }
+                    // Solid angle of this sample decides how blurry a lookup it needs
+                    let d = (cos_theta * cos_theta * (alpha2 - 1.0) + 1.0).powi(2);
+                    let pdf = alpha2 / (PI * d) / 4.0;
+                    let solid_angle = 1.0 / (SPECULAR_SAMPLES as f32 * pdf + 1e-4);
+                    sum += equirect.sample_lod(light, solid_angle) * n_dot_l;
+                    weight += n_dot_l;
+                }
+                if weight > 0.0 {
+                    sum / weight
+                } else {
+                    Vec3::ZERO
+                }
+            })
+        })
+        .collect();
+    cubemap(SPECULAR_SIZE, mips)
+}
+
+/// The nine real spherical harmonics up to band 2
+fn sh_basis(d: Vec3) -> [f32; 9] {
+    [
+        0.282_095,
+        0.488_603 * d.y,
+        0.488_603 * d.z,
+        0.488_603 * d.x,
+        1.092_548 * d.x * d.y,
+        1.092_548 * d.y * d.z,
+        0.315_392 * (3.0 * d.z * d.z - 1.0),
+        1.092_548 * d.x * d.z,
+        0.546_274 * (d.x * d.x - d.y * d.y),
+    ]
+}
+
+/// Diffuse cubemap of cosine-weighted irradiance divided by π, through spherical harmonics
+fn diffuse_cubemap(equirect: &Equirect) -> Image {
+    let level = equirect.level_for(TAU / 128.0);
+    let mut coefficients = [Vec3::ZERO; 9];
+    let texel_area = (TAU / level.width as f32) * (PI / level.height as f32);
+    for y in 0..level.height {
+        for x in 0..level.width {
+            let dir = level.direction(x, y);
+            // Rows near the poles cover less of the sphere
+            let solid_angle = texel_area * (1.0 - dir.y * dir.y).sqrt();
+            let radiance = level.texel(x, y) * solid_angle;
+            for (coefficient, basis) in coefficients.iter_mut().zip(sh_basis(dir)) {
+                *coefficient += radiance * basis;
+            }
+        }
+    }
+
+    // Cosine lobe convolution per band, already divided by π
+    const BANDS: [f32; 9] = [
+        1.0,
+        2.0 / 3.0,
+        2.0 / 3.0,
+        2.0 / 3.0,
+        0.25,
+        0.25,
+        0.25,
+        0.25,
+        0.25,
+    ];
+    let faces = cube_faces(DIFFUSE_SIZE, |normal| {
+        let irradiance: Vec3 = sh_basis(normal)
+            .into_iter()
+            .zip(coefficients)
+            .zip(BANDS)
+            .map(|((basis, coefficient), band)| coefficient * basis * band)
+            .sum();
+        irradiance.max(Vec3::ZERO)
+    });
+    cubemap(DIFFUSE_SIZE, vec![faces])
+}
+
+/// Decodes an equirectangular `.hdr` or `.exr` file and prefilters it into diffuse and specular
+/// cubemaps for [`EnvironmentMapLight`]. Slow for large images, so meant for a background task.
+pub fn load_equirect_environment(path: &Path) -> Result<(Image, Image), String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .ok_or("The file has no image extension")?;
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension(&extension),
+        CompressedImageFormats::NONE,
+        false,
+        ImageSampler::Default,
+        RenderAssetUsages::MAIN_WORLD,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let equirect = Equirect::from_image(&image)?;
+    Ok((diffuse_cubemap(&equirect), specular_cubemap(&equirect)))
+}
+
+/// Image-based lighting from an equirectangular HDR or EXR image
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct EnvironmentSettings {
+    /// Image to light the scene with; no image-based lighting without one
+    pub path: Option<PathBuf>,
+    /// cd/m² for a pixel value of 1
+    pub intensity: f32,
+    /// Degrees around the vertical axis
+    pub rotation: f32,
+    /// Whether the image is drawn behind the model
+    pub background: bool,
+}
+
+impl Default for EnvironmentSettings {
+    fn default() -> Self {
+        Self {
+            path: None,
+            intensity: 1_500.0,
+            rotation: 0.0,
+            background: true,
+        }
+    }
+}
+
+type ConversionTask = Task<Result<(Image, Image), String>>;
+
+/// Cubemaps converted from [`EnvironmentSettings::path`], and the conversion in progress
+#[derive(Resource, Default)]
+pub struct EnvironmentMaps {
+    /// Image the cubemaps were made from, with the diffuse and specular cubemap
+    loaded: Option<(PathBuf, Handle<Image>, Handle<Image>)>,
+    converting: Option<(PathBuf, ConversionTask)>,
+    /// Last image that failed to load, and why
+    pub error: Option<(PathBuf, String)>,
+    /// Open file dialog picking an image
+    pub dialog: Option<Task<DialogResponse>>,
+}
+
+impl EnvironmentMaps {
+    pub fn is_converting(&self) -> bool {
+        self.converting.is_some()
+    }
+}
+
+/// Converts the chosen environment image in the background whenever it changes
+pub fn load_environment_map(
+    mut lighting: ResMut<Lighting>,
+    mut maps: ResMut<EnvironmentMaps>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    // Polling through `bypass_change_detection` keeps the maps unchanged until there is a result
+    let picked = maps
+        .bypass_change_detection()
+        .dialog
+        .as_mut()
+        .and_then(|task| block_on(poll_once(task)));
+    if let Some(picked) = picked {
+        maps.dialog = None;
+        if let Some(handle) = picked {
+            lighting.environment.path = Some(handle.path().to_path_buf());
+            lighting.save();
+        }
+    }
+
+    let wanted = lighting.environment.path.clone();
+    let Some(path) = wanted else {
+        if maps.loaded.is_some() || maps.converting.is_some() {
+            maps.loaded = None;
+            maps.converting = None;
+        }
+        return;
+    };
+    let loaded = maps.loaded.as_ref().is_some_and(|(p, ..)| *p == path);
+    let converting = maps.converting.as_ref().is_some_and(|(p, _)| *p == path);
+    let failed = maps.error.as_ref().is_some_and(|(p, _)| *p == path);
+    if !loaded && !converting && !failed {
+        info!("Converting environment map '{}'", path.display());
+        let source = path.clone();
+        let task =
+            AsyncComputeTaskPool::get().spawn(async move { load_equirect_environment(&source) });
+        // Replacing a running conversion drops and so cancels it
+        maps.converting = Some((path, task));
+        return;
+    }
+
+    let Some(result) = maps
+        .bypass_change_detection()
+        .converting
+        .as_mut()
+        .and_then(|(_, task)| block_on(poll_once(task)))
+    else {
+        return;
+    };
+    let Some((path, _)) = maps.converting.take() else {
+        return;
+    };
+    match result {
+        Ok((diffuse, specular)) => {
+            info!("Environment map '{}' ready", path.display());
+            maps.loaded = Some((path, images.add(diffuse), images.add(specular)));
+            maps.error = None;
+        }
+        Err(e) => {
+            warn!("Failed to load environment map '{}': {}", path.display(), e);
+            maps.error = Some((path, e));
+        }
+    }
+}
+
+/// Puts the environment map on the main camera as lighting and, if wanted, as its background
+pub fn apply_environment_map(
+    mut commands: Commands,
+    lighting: Res<Lighting>,
+    maps: Res<EnvironmentMaps>,
+    camera: Single<Entity, With<PanOrbitCamera>>,
+) {
+    if !lighting.is_changed() && !maps.is_changed() {
+        return;
+    }
+    let settings = &lighting.environment;
+    let mut camera = commands.entity(*camera);
+    let Some((_, diffuse, specular)) = maps
+        .loaded
+        .as_ref()
+        .filter(|(path, ..)| settings.path.as_ref() == Some(path))
+    else {
+        camera.remove::<(EnvironmentMapLight, Skybox)>();
+        return;
+    };
+
+    let rotation = Quat::from_rotation_y(settings.rotation.to_radians());
+    camera.insert(EnvironmentMapLight {
+        diffuse_map: diffuse.clone(),
+        specular_map: specular.clone(),
+        intensity: settings.intensity,
+        rotation,
+        ..default()
+    });
+    if settings.background {
+        camera.insert(Skybox {
+            image: specular.clone(),
+            brightness: settings.intensity,
+            rotation,
+        });
+    } else {
+        camera.remove::<Skybox>();
+    }
+}
+
+/// Environment section of the lighting panel
+pub fn environment_ui(
+    ui: &mut egui::Ui,
+    settings: &mut EnvironmentSettings,
+    maps: &mut EnvironmentMaps,
+) {
+    ui.horizontal(|ui| {
+        let picking = maps.dialog.is_some();
+        if ui
+            .add_enabled(!picking, egui::Button::new("Load HDRI…"))
+            .clicked()
+        {
+            let mut dialog = rfd::AsyncFileDialog::new()
+                .set_title("Open environment map")
+                .add_filter("HDR images", &["hdr", "exr"]);
+            if let Some(dir) = settings.path.as_ref().and_then(|p| p.parent()) {
+                dialog = dialog.set_directory(dir);
+            }
+            let task = AsyncComputeTaskPool::get().spawn(async move { dialog.pick_file().await });
+            maps.dialog = Some(task);
+        }
+        if settings.path.is_some() && ui.button("Remove").clicked() {
+            settings.path = None;
+        }
+    });
+
+    let Some(path) = settings.path.clone() else {
+        ui.weak("No environment map");
+        return;
+    };
+    let name = path
+        .file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy();
+    match &maps.error {
+        Some((failed, e)) if *failed == path => {
+            ui.colored_label(egui::Color32::LIGHT_RED, format!("{name}: {e}"));
+            if ui.button("Retry").clicked() {
+                maps.error = None;
+            }
+        }
+        _ if maps.is_converting() => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!("Converting {name}…"));
+            });
+        }
+        _ => {
+            ui.label(name.to_string())
+                .on_hover_text(path.display().to_string());
+        }
+    }
+
+    egui::Grid::new("environment_settings")
+        .num_columns(2)
+        .spacing([12.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("Intensity");
+            ui.add(
+                egui::Slider::new(&mut settings.intensity, 0.0..=20_000.0)
+                    .logarithmic(true)
+                    .suffix(" cd/m²"),
+            );
+            ui.end_row();
+
+            ui.label("Rotation");
+            ui.add(egui::Slider::new(&mut settings.rotation, 0.0..=360.0).suffix("°"));
+            ui.end_row();
+
+            ui.label("Background");
+            ui.checkbox(&mut settings.background, "");
+            ui.end_row();
+        });
+}
@@ -1,27 +1,45 @@
 use bevy::{
     color::palettes, prelude::*, scene::SceneInstanceReady
 };
+use bevy::asset::AssetPath;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
 use directories::UserDirs;
 use serde::{Deserialize, Serialize};
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::SystemTime,
+};
 
 
-use crate::objects::{ColorOverride};
+use crate::{
+    objects::ColorOverride,
+    recent::RecentFiles,
+    stats::ModelStatsCache,
+    thumbnails::ThumbnailModel,
+};
 
+/// Directory being browsed. Kept as a path so non-UTF-8 names stay reachable.
 #[derive(Resource)]
-pub struct Directory(pub String);
+pub struct Directory(pub PathBuf);
 
 impl Default for Directory {
     fn default() -> Self {
-        Self(".".to_string())
+        Self(PathBuf::from("."))
     }
 }
 
-#[derive(Resource, Debug, Clone, PartialEq, Eq)]
-pub struct OpenFile(pub String);
+/// Model shown in the viewer, empty when nothing is open
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Default)]
+pub struct OpenFile(pub PathBuf);
 
-impl Default for OpenFile {
-    fn default() -> Self {
-        Self("".to_string())
+impl OpenFile {
+    pub fn is_empty(&self) -> bool {
+        self.0.as_os_str().is_empty()
     }
 }
 
@@ -30,9 +48,15 @@ pub enum SortMode {
     Name,
     Size,
     Date,
+    /// File extension, then name
+    Extension,
+    /// Most polygons first; files never loaded go last
+    Polygons,
+    /// Most recently opened first; files never opened go last
+    LastOpened,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct FileList(pub Vec<FileEntry>);
 
 #[derive(Resource)]
@@ -53,24 +77,315 @@ impl Default for ShowEditFileName {
 
 #[derive(Serialize, Deserialize)]
 pub struct FileEntry {
-    pub name: String,
-    pub last_modified: u64,
-    //pub size: u64
+    /// File name as stored on disk; convert only for display
+    pub name: OsString,
+    pub is_dir: bool,
+    /// Modification time, when the file system reports one
+    pub modified: Option<SystemTime>,
+    /// Size in bytes, 0 for directories
+    pub size: u64,
+    /// Target of a symbolic link, for display
+    pub link_target: Option<String>,
+    /// Why the entry could not be read, e.g. a broken link or missing permissions
+    pub error: Option<String>,
+}
+
+/// File extensions the viewer can open
+pub const MODEL_EXTENSIONS: [&str; 2] = ["glb", "gltf"];
+
+/// Whether the path has a supported model extension (case-insensitive)
+pub fn is_model_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| MODEL_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Picks a name inside `dir` that does not exist yet, appending " copy", " copy 2", ... to the stem
+pub fn unique_destination(dir: &Path, file_name: &OsStr) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let original = Path::new(file_name);
+    let stem = original.file_stem().unwrap_or(file_name);
+
+    let mut n = 1;
+    loop {
+        let mut name = stem.to_os_string();
+        if n == 1 {
+            name.push(" copy");
+        } else {
+            name.push(format!(" copy {}", n));
+        }
+        if let Some(extension) = original.extension() {
+            name.push(".");
+            name.push(extension);
+        }
+        let candidate = dir.join(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Copies a file or a whole folder into `dir` without overwriting anything. Returns the new path.
+pub fn copy_into_dir(src: &Path, dir: &Path) -> Result<PathBuf, String> {
+    let file_name = src
+        .file_name()
+        .ok_or_else(|| format!("Cannot copy '{}': no file name", src.display()))?;
+    if src.is_dir() && dir.starts_with(src) {
+        return Err(format!("Cannot copy '{}' into itself", src.display()));
+    }
+    let dest = unique_destination(dir, file_name);
+
+    if src.is_dir() {
+        for entry in walkdir::WalkDir::new(src) {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let relative = entry.path().strip_prefix(src).map_err(|e| e.to_string())?;
+            let target = dest.join(relative);
+            let result = if entry.file_type().is_dir() {
+                std::fs::create_dir_all(&target)
+            } else {
+                std::fs::copy(entry.path(), &target).map(|_| ())
+            };
+            result.map_err(|e| format!("Failed to copy '{}': {}", entry.path().display(), e))?;
+        }
+    } else {
+        std::fs::copy(src, &dest)
+            .map_err(|e| format!("Failed to copy '{}': {}", src.display(), e))?;
+    }
+
+    Ok(dest)
+}
+
+/// Moves a file or folder into `dir` without overwriting anything. Returns the new path.
+pub fn move_into_dir(src: &Path, dir: &Path) -> Result<PathBuf, String> {
+    let file_name = src
+        .file_name()
+        .ok_or_else(|| format!("Cannot move '{}': no file name", src.display()))?;
+    if src.parent() == Some(dir) {
+        return Err(format!("'{}' is already in {}", src.display(), dir.display()));
+    }
+    if src.is_dir() && dir.starts_with(src) {
+        return Err(format!("Cannot move '{}' into itself", src.display()));
+    }
+    let dest = unique_destination(dir, file_name);
+
+    match std::fs::rename(src, &dest) {
+        Ok(()) => Ok(dest),
+        // Renaming fails across file systems, so copy and remove the original instead
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            let dest = copy_into_dir(src, dir)?;
+            let result = if src.is_dir() {
+                std::fs::remove_dir_all(src)
+            } else {
+                std::fs::remove_file(src)
+            };
+            result
+                .map_err(|e| format!("Copied but failed to remove '{}': {}", src.display(), e))?;
+            Ok(dest)
+        }
+        Err(e) => Err(format!("Failed to move '{}': {}", src.display(), e)),
+    }
 }
 
-pub fn file_dir_path(dir: String, file: String) -> String {
-    let path = std::path::Path::new(&dir).join(file);
-    path.to_str().unwrap_or("").to_string()
+/// Parent of `dir`, resolving relative paths such as `.` first. `None` at the file system root.
+pub fn parent_dir(dir: &Path) -> Option<PathBuf> {
+    let path = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    path.parent().map(Path::to_path_buf)
+}
+
+pub fn file_dir_path(dir: &Path, file: &OsStr) -> PathBuf {
+    dir.join(file)
+}
+
+/// Entries are handed from the listing task to the main world in batches of this size
+const LISTING_BATCH_SIZE: usize = 256;
+
+/// Data shared between a running listing task and [`poll_dir_listing`]
+#[derive(Default)]
+struct ListingShared {
+    entries: Vec<FileEntry>,
+    error: Option<String>,
+    done: bool,
+}
+
+/// Background listing of the current [`Directory`] on the [`AsyncComputeTaskPool`]
+#[derive(Resource, Default)]
+pub struct DirListing {
+    task: Option<Task<()>>,
+    shared: Arc<Mutex<ListingShared>>,
+    cancel: Arc<AtomicBool>,
+    /// Directory of the last started listing
+    listed_dir: PathBuf,
+    /// Stream batches straight into the [`FileList`]; refreshes instead swap the list once done
+    streaming: bool,
+    pending: Vec<FileEntry>,
+    pub loading: bool,
+    pub loaded_count: usize,
+    /// Why the last listing failed, if it did
+    pub error: Option<String>,
+}
+
+impl DirListing {
+    fn start(&mut self, dir: &Path, streaming: bool) {
+        self.cancel();
+
+        let shared = Arc::new(Mutex::new(ListingShared::default()));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let task_dir = dir.to_path_buf();
+        let task_shared = shared.clone();
+        let task_cancel = cancel.clone();
+        self.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+            list_dir_streaming(&task_dir, &task_cancel, &task_shared);
+        }));
+
+        self.shared = shared;
+        self.cancel = cancel;
+        self.listed_dir = dir.to_path_buf();
+        self.streaming = streaming;
+        self.pending.clear();
+        self.loading = true;
+        self.loaded_count = 0;
+        self.error = None;
+    }
+
+    /// Whether `dir` has been listed completely, so a restored scroll offset has rows to land on
+    pub fn is_complete(&self, dir: &Path) -> bool {
+        !self.loading && self.listed_dir == dir
+    }
+
+    /// Stops the running listing, if any. Entries already streamed stay in the [`FileList`].
+    pub fn cancel(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.task = None;
+        self.loading = false;
+    }
 }
 
 pub fn check_dir_changed(
     dir: Res<Directory>,
     mut file_list: ResMut<FileList>,
     sort_mode: Res<SortMode>,
+    mut listing: ResMut<DirListing>,
+    model_stats: Res<ModelStatsCache>,
+    recent_files: Res<RecentFiles>,
 ) {
-    if dir.is_changed() || sort_mode.is_changed() {
-        file_list.0 = dir_list_approved_files(&dir.0, *sort_mode);
+    if dir.is_changed() {
+        // Re-listing the same directory keeps the old entries visible until the new list is complete
+        let refresh = listing.listed_dir == dir.0;
+        if !refresh {
+            file_list.0.clear();
+        }
+        listing.start(&dir.0, !refresh);
+    } else if sort_mode.is_changed()
+        || (*sort_mode == SortMode::Polygons && model_stats.is_changed())
+        || (*sort_mode == SortMode::LastOpened && recent_files.is_changed())
+    {
+        let keys = SortKeys {
+            dir: &dir.0,
+            model_stats: &model_stats,
+            recent_files: &recent_files,
+        };
+        // Most of these changes, like pinning a recent file, leave the order as it was; the list
+        // is only marked changed when it really moved, so the grid keeps its keyboard focus
+        let list = file_list.bypass_change_detection();
+        let before: Vec<OsString> = list.0.iter().map(|e| e.name.clone()).collect();
+        sort_entries(&mut list.0, *sort_mode, &keys);
+        if !list.0.iter().map(|e| &e.name).eq(&before) {
+            file_list.set_changed();
+        }
+    }
+}
+
+/// Moves entries found by the listing task into the [`FileList`]
+pub fn poll_dir_listing(
+    mut listing: ResMut<DirListing>,
+    mut file_list: ResMut<FileList>,
+    sort_mode: Res<SortMode>,
+    model_stats: Res<ModelStatsCache>,
+    recent_files: Res<RecentFiles>,
+) {
+    if listing.task.is_none() {
+        return;
+    }
+
+    let (mut new_entries, error, done) = {
+        let mut shared = listing.shared.lock().unwrap();
+        (std::mem::take(&mut shared.entries), shared.error.take(), shared.done)
+    };
+    if error.is_some() {
+        listing.error = error;
+    }
+    listing.loaded_count += new_entries.len();
+
+    if listing.streaming {
+        if !new_entries.is_empty() {
+            file_list.0.append(&mut new_entries);
+            let keys = SortKeys {
+                dir: &listing.listed_dir,
+                model_stats: &model_stats,
+                recent_files: &recent_files,
+            };
+            sort_entries(&mut file_list.0, *sort_mode, &keys);
+        }
+    } else {
+        listing.pending.append(&mut new_entries);
+        if done {
+            file_list.0 = std::mem::take(&mut listing.pending);
+            let keys = SortKeys {
+                dir: &listing.listed_dir,
+                model_stats: &model_stats,
+                recent_files: &recent_files,
+            };
+            sort_entries(&mut file_list.0, *sort_mode, &keys);
+        }
+    }
+
+    if done {
+        listing.task = None;
+        listing.loading = false;
+    }
+}
+
+fn list_dir_streaming(path: &Path, cancel: &AtomicBool, shared: &Mutex<ListingShared>) {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("Failed to read directory '{}': {}", path.display(), e);
+            let mut shared = shared.lock().unwrap();
+            shared.error = Some(format!("Cannot read {}: {}", path.display(), e));
+            shared.done = true;
+            return;
+        }
+    };
+
+    let mut batch = Vec::with_capacity(LISTING_BATCH_SIZE);
+    for e in entries {
+        let e = match e {
+            Ok(e) => e,
+            Err(err) => {
+                warn!("Skipping unreadable entry in '{}': {}", path.display(), err);
+                continue;
+            }
+        };
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(entry) = approved_entry(&e) {
+            batch.push(entry);
+        }
+        if batch.len() >= LISTING_BATCH_SIZE {
+            shared.lock().unwrap().entries.append(&mut batch);
+        }
     }
+
+    let mut shared = shared.lock().unwrap();
+    shared.entries.append(&mut batch);
+    shared.done = true;
 }
 
 // pub fn move_file(src: String, dest: String) -> Result {
@@ -80,75 +395,188 @@ pub fn check_dir_changed(
 //     }
 // }
 
-pub fn dir_list_approved_files(path: &str, sort_mode: SortMode) -> Vec<FileEntry> {
-    // Define accepted file extensions
-    let accepted_extensions = ["glb", "gltf"];
+/// Builds the entry for a directory or model file, skipping hidden and unsupported files
+fn approved_entry(e: &std::fs::DirEntry) -> Option<FileEntry> {
+    if e.file_name().to_string_lossy().starts_with(".") {
+        return None;
+    }
+    //TODO hide hidden folders
+    let path = e.path();
+    let is_symlink = e.file_type().map(|ft| ft.is_symlink()).unwrap_or(false);
+    let link_target = if is_symlink {
+        std::fs::read_link(&path)
+            .ok()
+            .map(|target| target.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    // Follow links so a link to a folder is browsable; a broken link or an unreadable
+    // entry is still listed, carrying the reason instead of failing the whole listing
+    let metadata = if is_symlink {
+        std::fs::metadata(&path).map_err(|err| format!("Broken link: {}", err))
+    } else {
+        e.metadata().map_err(|err| err.to_string())
+    };
+    let is_dir = match &metadata {
+        Ok(md) => md.is_dir(),
+        Err(_) => e.file_type().map(|ft| ft.is_dir()).unwrap_or(false),
+    };
+
+    // Allow directories, otherwise check if file has an accepted extension (case-insensitive).
+    // A broken link may point at anything, e.g. a folder, so it is listed whatever its name.
+    let broken_link = is_symlink && metadata.is_err();
+    if !is_dir && !broken_link && !is_model_file(&path) {
+        return None;
+    }
+
+    let modified = metadata.as_ref().ok().and_then(|md| md.modified().ok());
+
+    let size = match &metadata {
+        Ok(md) if !is_dir => md.len(),
+        _ => 0,
+    };
+
+    Some(FileEntry {
+        name: e.file_name(),
+        is_dir,
+        modified,
+        size,
+        link_target,
+        error: metadata.err(),
+    })
+}
+
+/// Lookups needed by sort modes that go beyond what the directory listing provides
+pub struct SortKeys<'a> {
+    /// Directory the entries were listed from
+    pub dir: &'a Path,
+    pub model_stats: &'a ModelStatsCache,
+    pub recent_files: &'a RecentFiles,
+}
+
+/// Sorts entries with directories first, falling back to natural name order for ties
+pub fn sort_entries(entries: &mut [FileEntry], sort_mode: SortMode, keys: &SortKeys) {
+    let by_name = |a: &FileEntry, b: &FileEntry| {
+        natural_cmp(&a.name.to_string_lossy(), &b.name.to_string_lossy())
+            .then_with(|| a.name.cmp(&b.name))
+    };
+    let dirs_first = |a: &FileEntry, b: &FileEntry| b.is_dir.cmp(&a.is_dir);
+
+    match sort_mode {
+        SortMode::Name => entries.sort_by(|a, b| dirs_first(a, b).then_with(|| by_name(a, b))),
+        SortMode::Size => entries.sort_by(|a, b| {
+            dirs_first(a, b)
+                .then_with(|| b.size.cmp(&a.size))
+                .then_with(|| by_name(a, b))
+        }),
+        SortMode::Date => entries.sort_by(|a, b| {
+            dirs_first(a, b)
+                // Newest first, unknown times last
+                .then_with(|| b.modified.cmp(&a.modified))
+                .then_with(|| by_name(a, b))
+        }),
+        SortMode::Extension => entries.sort_by_cached_key(|e| {
+            let extension = Path::new(&e.name)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            (!e.is_dir, extension, NaturalKey::new(&e.name))
+        }),
+        SortMode::Polygons => entries.sort_by_cached_key(|e| {
+            // Reverse puts the largest counts first and unknown counts (None) last
+            let polygons = keys
+                .model_stats
+                .get(&keys.dir.join(&e.name))
+                .map(|stats| stats.polygon_count);
+            (!e.is_dir, std::cmp::Reverse(polygons), NaturalKey::new(&e.name))
+        }),
+        SortMode::LastOpened => entries.sort_by_cached_key(|e| {
+            let opened = keys.recent_files.last_opened(&keys.dir.join(&e.name));
+            (!e.is_dir, std::cmp::Reverse(opened), NaturalKey::new(&e.name))
+        }),
+    }
+}
+
+/// Compares names the way people read them: case-insensitive, with runs of digits
+/// compared by value so `rock_2` comes before `rock_10`
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering::*;
+
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        let ordering = match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Equal,
+            (None, Some(_)) => return Less,
+            (Some(_), None) => return Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x_digits = take_digits(&mut a_chars);
+                let y_digits = take_digits(&mut b_chars);
+                let x_value = x_digits.trim_start_matches('0');
+                let y_value = y_digits.trim_start_matches('0');
+                // Without leading zeros a longer run is a bigger number; `07` after `7`
+                x_value
+                    .len()
+                    .cmp(&y_value.len())
+                    .then_with(|| x_value.cmp(y_value))
+                    .then_with(|| x_digits.len().cmp(&y_digits.len()))
+            }
+            (Some(x), Some(y)) => {
+                a_chars.next();
+                b_chars.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            }
+        };
+        if ordering != Equal {
+            return ordering;
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    }
+    digits
+}
+
+/// Owned sort key ordering like [`natural_cmp`], for `sort_by_cached_key`
+#[derive(PartialEq, Eq)]
+struct NaturalKey(String);
 
+impl NaturalKey {
+    fn new(name: &OsStr) -> Self {
+        Self(name.to_string_lossy().to_string())
+    }
+}
+
+impl PartialOrd for NaturalKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NaturalKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        natural_cmp(&self.0, &other.0).then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+/// Lists a directory synchronously. Prefer [`DirListing`] for anything driven by the UI.
+pub fn dir_list_approved_files(path: &Path, sort_mode: SortMode, keys: &SortKeys) -> Vec<FileEntry> {
     match std::fs::read_dir(path) {
         Ok(entries) => {
-            let mut items: Vec<(bool, FileEntry)> = entries
+            let mut items: Vec<FileEntry> = entries
                 .filter_map(|e| e.ok())
-                .filter(|e| {
-                    if e.file_name().to_string_lossy().starts_with(".") {
-                        return false;
-                    }
-                    // Allow directories
-                    //TODO hide hidden folders
-                    if e.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
-                        return true;
-                    }
-    // Check if file has an accepted extension (case-insensitive)
-    e.path()
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| accepted_extensions.contains(&ext.to_lowercase().as_str()))
-        .unwrap_or(false)
-                })
-                // .map(|e| e.file_name().to_string_lossy().to_string())
-                //.collect();
-                .map(|e| {
-                    //if e.exis
-                    let is_dir = e.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
-                    let fe = FileEntry {
-                        name: e.file_name().to_string_lossy().to_string(),
-                        last_modified: e
-                            .metadata()
-                            .unwrap()
-                            .modified()
-                            .unwrap()
-                            .elapsed()
-                            .unwrap_or_default()
-                            .as_secs(),
-                    };
-                    (is_dir, fe)
-                })
+                .filter_map(|e| approved_entry(&e))
                 .collect();
-
-            match sort_mode {
-                SortMode::Name => items.sort_by(|a, b| {
-                    match (a.0, b.0) {
-                        (true, false) => std::cmp::Ordering::Less, // dirs before files
-                        (false, true) => std::cmp::Ordering::Greater,
-                        _ => a.1.name.to_lowercase().cmp(&b.1.name.to_lowercase()),
-                    }
-                }),
-                SortMode::Size => todo!(),
-                SortMode::Date => items.sort_by(|a, b| {
-                    match (a.0, b.0) {
-                        (true, false) => std::cmp::Ordering::Less, // dirs before files
-                        (false, true) => std::cmp::Ordering::Greater,
-                        _ => a.1.last_modified.cmp(&b.1.last_modified),
-                    }
-                }),
-            }
-
+            sort_entries(&mut items, sort_mode, keys);
             items
-                .into_iter()
-                .map(|(_, file_entry)| file_entry)
-                .collect()
         }
         Err(e) => {
-            error!("Failed to read directory '{}': {}", path, e);
+            error!("Failed to read directory '{}': {}", path.display(), e);
             Vec::new()
         }
     }
@@ -171,8 +599,13 @@ pub fn check_open_file_changed(
             println!("Despawning old GLTF entity: {:?}", old_entity);
             commands.entity(old_entity).despawn();
         }
+        if open_file.is_empty() {
+            current_gltf.0 = None;
+            return;
+        }
 
-        let file_name = format!("{}#Scene0", open_file.0);
+        // Build the asset path from the raw path so non-UTF-8 names load too
+        let file_name = AssetPath::from_path_buf(open_file.0.clone()).with_label("Scene0");
         println!("Filename: {}", file_name);
         let scene = asset_server.load(file_name);
         let scale = 1.0;
@@ -195,58 +628,69 @@ pub fn check_open_file_changed(
     }
 }
 
-pub fn home_dir() -> String {
-    //let path = "";
-
-    let user_dirs = UserDirs::new();
-    // let desktop_dir = match &user_dirs {
-    //     Some(user_dirs) => user_dirs.desktop_dir(),
-    //     None => {
-    //         println!("Could not retrieve user directories.");
-    //         None
-    //     }
-    // }
-    // .unwrap();
-
-    let home_dir = match &user_dirs {
-        Some(user_dirs) => user_dirs.home_dir(),
-        None => {
-            println!("Could not retrieve user directories.");
-            panic!()
-        }
-    };
+/// The user's home directory, falling back to the working directory if it cannot be resolved
+pub fn home_dir() -> PathBuf {
+    if let Some(user_dirs) = UserDirs::new() {
+        return user_dirs.home_dir().to_path_buf();
+    }
 
-    //let p = home_dir.join(path);
-    home_dir.to_string_lossy().to_string()
+    warn!("Could not retrieve user directories, falling back to the current directory");
+    dirs::home_dir()
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."))
 }
 
+/// Counts the meshes of a freshly spawned model, for the info panel and the [`ModelStatsCache`]
 pub fn check_model_loaded(
     trigger: On<SceneInstanceReady>,
     meshes: Res<Assets<Mesh>>,
-    _scene_assets: Res<Assets<Scene>>,
-    _asset_server: Res<AssetServer>,
+    children: Query<&Children>,
+    mesh_handles: Query<&Mesh3d>,
+    thumbnail_models: Query<&ThumbnailModel>,
+    current_gltf: Res<CurrentGltfEntity>,
+    open_file: Res<OpenFile>,
     mut model_info: ResMut<ModelInfo>,
+    mut model_stats: ResMut<ModelStatsCache>,
 ) {
-    let _entity = trigger.event().entity;
+    let entity = trigger.event().entity;
+
+    // Thumbnail renders load the same files, so they fill the cache too
+    let (path, is_viewer) = if let Ok(model) = thumbnail_models.get(entity) {
+        (model.file_path.clone(), false)
+    } else if current_gltf.0 == Some(entity) {
+        (open_file.0.clone(), true)
+    } else {
+        return;
+    };
 
-    println!("Model loaded!");
+    debug!("Model loaded: {}", path.display());
 
+    // Only this scene's meshes; other models and thumbnails share the mesh assets
     let mut vertex_count: usize = 0;
     let mut polygon_count: usize = 0;
-    for (mesh_handle, mesh) in meshes.iter() {
+    for descendant in children.iter_descendants(entity) {
+        let Some(mesh) = mesh_handles
+            .get(descendant)
+            .ok()
+            .and_then(|handle| meshes.get(&handle.0))
+        else {
+            continue;
+        };
         vertex_count += mesh.count_vertices();
         let index_count = match mesh.indices() {
             Some(i) => i.len(),
             None => 0,
         };
         polygon_count += index_count / 3;
-        println!(
-            "Mesh {:?}: {} vertices, {} polygons",
-            mesh_handle, vertex_count, polygon_count
-        );
     }
-    model_info.vertex_count = vertex_count;
-    model_info.polygon_count = polygon_count;
+
+    if is_viewer {
+        model_info.vertex_count = vertex_count;
+        model_info.polygon_count = polygon_count;
+    }
+    model_stats
+        .bypass_change_detection()
+        .insert(&path, polygon_count, vertex_count);
 }
 
 #[derive(Resource, Default)]
@@ -257,7 +701,7 @@ pub struct ModelInfo {
 
 
 
-pub fn open_finder(path: String) -> Result<(), String> {
+pub fn open_finder(path: &Path) -> Result<(), String> {
     println!("Opening in Finder: {:?}", path);
     
     // On macOS, use 'open -R' to reveal the file in Finder
@@ -267,32 +711,62 @@ pub fn open_finder(path: String) -> Result<(), String> {
         use std::process::Command;
         match Command::new("open")
             .arg("-R")
-            .arg(&path)
+            .arg(path)
             .spawn()
         {
             Ok(_) => Ok(()),
             Err(err) => Err(format!(
                 "Failed to reveal '{}' in Finder: {}",
-                path, err
+                path.display(), err
             )),
         }
     }
     
     #[cfg(not(target_os = "macos"))]
     {
-        use std::path::Path;
         // On other platforms, open the parent directory
-        let dir = Path::new(&path)
-            .parent()
-            .and_then(|p| p.to_str())
-            .unwrap_or(".");
+        let dir = path.parent().unwrap_or(Path::new("."));
         
         match open::that(dir) {
             Ok(()) => Ok(()),
             Err(err) => Err(format!(
                 "Failed to open directory '{}': {}",
-                dir, err
+                dir.display(), err
             )),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::natural_cmp;
+    use std::cmp::Ordering::*;
+
+    #[test]
+    fn numbers_compare_by_value() {
+        assert_eq!(natural_cmp("rock_2", "rock_10"), Less);
+        assert_eq!(natural_cmp("rock_10", "rock_2"), Greater);
+        assert_eq!(natural_cmp("rock_10", "rock_10"), Equal);
+    }
+
+    #[test]
+    fn leading_zeros_sort_after_the_plain_number() {
+        assert_eq!(natural_cmp("7", "07"), Less);
+        assert_eq!(natural_cmp("07", "7"), Greater);
+        assert_eq!(natural_cmp("07", "8"), Less);
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        assert_eq!(natural_cmp("Rock", "rock"), Equal);
+        assert_eq!(natural_cmp("apple", "Banana"), Less);
+        assert_eq!(natural_cmp("ROCK_2", "rock_10"), Less);
+    }
+
+    #[test]
+    fn digits_at_the_end() {
+        assert_eq!(natural_cmp("rock", "rock1"), Less);
+        assert_eq!(natural_cmp("rock9", "rock10"), Less);
+        assert_eq!(natural_cmp("rock.glb", "rock2.glb"), Less);
+    }
+}
@@ -0,0 +1,248 @@
+use bevy::{
+    camera::primitives::Aabb,
+    picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings},
+    prelude::*,
+    window::{CursorGrabMode, CursorOptions, PrimaryWindow},
+};
+use bevy_enhanced_input::condition::press::Press;
+use bevy_enhanced_input::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use std::{collections::HashSet, f32::consts::FRAC_PI_2};
+
+use crate::{
+    bounds::model_bounds,
+    files::CurrentGltfEntity,
+    keybindings::{KeyBindings, ViewerAction},
+    ui::{ToggleFlyCamera, ViewMode},
+};
+
+/// Seconds the fly camera needs to cross the model at normal speed
+const CROSSING_SECONDS: f32 = 4.0;
+/// Speed multiplier while boost is held
+const BOOST: f32 = 4.0;
+/// Speed without a loaded model, in units per second
+const DEFAULT_SPEED: f32 = 2.0;
+
+/// WASD + mouse-look movement, active while [`FlyCamera::active`]. Spawned on the
+/// [`crate::ui::UiKeyAction`] entity.
+#[derive(Component)]
+pub struct FlyKeyAction;
+
+#[derive(InputAction)]
+#[action_output(Vec2)]
+pub struct FlyMove;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct FlyUp;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct FlyDown;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct FlyBoost;
+
+#[derive(InputAction)]
+#[action_output(Vec2)]
+pub struct FlyLook;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct FlyExit;
+
+/// Whether the main camera flies freely instead of orbiting, and how fast
+#[derive(Resource)]
+pub struct FlyCamera {
+    pub active: bool,
+    /// Units per second, set from the model size when flying starts
+    speed: f32,
+}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        Self {
+            active: false,
+            speed: DEFAULT_SPEED,
+        }
+    }
+}
+
+/// Fly actions with the movement keys from [`KeyBindings`]. Mouse motion and the sticks look
+/// and move, Escape returns to orbiting; those are not rebindable.
+pub fn fly_actions(bindings: &KeyBindings) -> impl Bundle {
+    let [forward, backward, left, right] = [
+        ViewerAction::FlyForward,
+        ViewerAction::FlyBackward,
+        ViewerAction::FlyLeft,
+        ViewerAction::FlyRight,
+    ]
+    .map(|action| bindings.get(action));
+    let directions = |slot: usize| Cardinal {
+        north: forward[slot],
+        east: right[slot],
+        south: backward[slot],
+        west: left[slot],
+    };
+
+    actions!(FlyKeyAction[
+        (
+            Action::<FlyMove>::new(),
+            DeadZone::default(),
+            Bindings::spawn((directions(0), directions(1), directions(2), Axial::left_stick())),
+        ),
+        (Action::<FlyUp>::new(), bindings.spawn(ViewerAction::FlyUp)),
+        (Action::<FlyDown>::new(), bindings.spawn(ViewerAction::FlyDown)),
+        (Action::<FlyBoost>::new(), bindings.spawn(ViewerAction::FlyBoost)),
+        (
+            Action::<FlyLook>::new(),
+            Bindings::spawn((
+                Spawn((Binding::mouse_motion(), Scale::splat(0.1), Negate::all())),
+                Axial::right_stick().with((Scale::splat(2.0), Negate::x())),
+            )),
+        ),
+        (Action::<FlyExit>::new(), Press::new(1.0), bindings![KeyCode::Escape]),
+    ])
+}
+
+fn speed(fly: &FlyCamera, boost: &Action<FlyBoost>, time: &Time) -> f32 {
+    let boost = if **boost { BOOST } else { 1.0 };
+    fly.speed * boost * time.delta_secs()
+}
+
+pub fn handle_fly_move(
+    trigger: On<Fire<FlyMove>>,
+    fly: Res<FlyCamera>,
+    time: Res<Time>,
+    boost: Single<&Action<FlyBoost>>,
+    mut camera: Single<&mut Transform, With<PanOrbitCamera>>,
+) {
+    let step = speed(&fly, &boost, &time);
+    let movement = camera.right() * trigger.value.x + camera.forward() * trigger.value.y;
+    camera.translation += movement * step;
+}
+
+pub fn handle_fly_up(
+    _trigger: On<Fire<FlyUp>>,
+    fly: Res<FlyCamera>,
+    time: Res<Time>,
+    boost: Single<&Action<FlyBoost>>,
+    mut camera: Single<&mut Transform, With<PanOrbitCamera>>,
+) {
+    camera.translation.y += speed(&fly, &boost, &time);
+}
+
+pub fn handle_fly_down(
+    _trigger: On<Fire<FlyDown>>,
+    fly: Res<FlyCamera>,
+    time: Res<Time>,
+    boost: Single<&Action<FlyBoost>>,
+    mut camera: Single<&mut Transform, With<PanOrbitCamera>>,
+) {
+    camera.translation.y -= speed(&fly, &boost, &time);
+}
+
+pub fn handle_fly_look(
+    trigger: On<Fire<FlyLook>>,
+    mut camera: Single<&mut Transform, With<PanOrbitCamera>>,
+) {
+    let (yaw, pitch, _) = camera.rotation.to_euler(EulerRot::YXZ);
+    let yaw = yaw + trigger.value.x.to_radians();
+    // Stop short of straight up or down, where yaw flips
+    let pitch = (pitch + trigger.value.y.to_radians()).clamp(-FRAC_PI_2 + 0.01, FRAC_PI_2 - 0.01);
+    camera.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+}
+
+pub fn handle_toggle_fly_camera(
+    _trigger: On<Fire<ToggleFlyCamera>>,
+    mut fly: ResMut<FlyCamera>,
+) {
+    fly.active = !fly.active;
+}
+
+pub fn handle_fly_exit(_trigger: On<Fire<FlyExit>>, mut fly: ResMut<FlyCamera>) {
+    fly.active = false;
+}
+
+/// Switches the main camera between orbiting and flying when [`FlyCamera::active`] changes.
+/// Flying starts at the orbit camera's position with a speed scaled to the model; orbiting
+/// resumes around the model point in the middle of the view.
+pub fn apply_fly_mode(
+    mut fly: ResMut<FlyCamera>,
+    view_mode: Res<ViewMode>,
+    camera: Single<(&mut PanOrbitCamera, &Transform)>,
+    mut cursor: Single<&mut CursorOptions, With<PrimaryWindow>>,
+    current_gltf: Res<CurrentGltfEntity>,
+    children: Query<&Children>,
+    aabbs: Query<(&Aabb, &GlobalTransform)>,
+    mut ray_cast: MeshRayCast,
+) {
+    // The fly camera only makes sense while the 3D view is shown
+    if fly.active && *view_mode != ViewMode::Model {
+        fly.active = false;
+    }
+    let (mut orbit, transform) = camera.into_inner();
+    if fly.active != orbit.enabled {
+        return;
+    }
+
+    let bounds = current_gltf
+        .0
+        .and_then(|root| model_bounds(root, &children, &aabbs));
+
+    if fly.active {
+        fly.speed = bounds
+            .map(|b| (b.size() / CROSSING_SECONDS).max(0.01))
+            .unwrap_or(DEFAULT_SPEED);
+        orbit.enabled = false;
+        grab_cursor(&mut cursor, true);
+        info!("Fly camera on, {:.2} units/s", fly.speed);
+        return;
+    }
+
+    // Orbit around what is under the crosshair, or the point ahead as far away as the model
+    let model: HashSet<Entity> = current_gltf
+        .0
+        .map(|root| children.iter_descendants(root).collect())
+        .unwrap_or_default();
+    let filter = |entity: Entity| model.contains(&entity);
+    let settings = MeshRayCastSettings::default().with_filter(&filter);
+    let ray = Ray3d::new(transform.translation, transform.forward());
+    let focus = match ray_cast.cast_ray(ray, &settings).first() {
+        Some((_, hit)) => hit.point,
+        None => {
+            let distance = bounds
+                .map(|b| b.center().distance(transform.translation))
+                .unwrap_or(orbit.target_radius);
+            transform.translation + transform.forward() * distance
+        }
+    };
+
+    // Yaw and pitch that put the orbit camera exactly where the fly camera is
+    let offset = transform.translation - focus;
+    let radius = offset.length().max(0.01);
+    let yaw = offset.x.atan2(offset.z);
+    let pitch = (offset.y / radius).clamp(-1.0, 1.0).asin();
+    orbit.focus = focus;
+    orbit.target_focus = focus;
+    orbit.radius = Some(radius);
+    orbit.target_radius = radius;
+    orbit.yaw = Some(yaw);
+    orbit.target_yaw = yaw;
+    orbit.pitch = Some(pitch);
+    orbit.target_pitch = pitch;
+    orbit.enabled = true;
+    orbit.force_update = true;
+    grab_cursor(&mut cursor, false);
+    info!("Fly camera off, orbiting {:?}", focus);
+}
+
+fn grab_cursor(cursor: &mut CursorOptions, grab: bool) {
+    cursor.grab_mode = if grab {
+        CursorGrabMode::Locked
+    } else {
+        CursorGrabMode::None
+    };
+    cursor.visible = !grab;
+}
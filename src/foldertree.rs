@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{load_json, save_json},
+    files::{home_dir, natural_cmp},
+};
+
+const FAVORITE_FOLDERS_FILE: &str = "favorite_folders.json";
+
+/// Folders pinned as extra roots of the folder tree, persisted in the config dir
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct FavoriteFolders {
+    pub folders: Vec<PathBuf>,
+}
+
+impl FavoriteFolders {
+    pub fn load() -> Self {
+        load_json(FAVORITE_FOLDERS_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = save_json(FAVORITE_FOLDERS_FILE, self) {
+            warn!("Failed to save favorite folders: {}", e);
+        }
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.folders.iter().any(|f| f == path)
+    }
+
+    /// Adds `path` to the favorites, or removes it if it is already one
+    pub fn toggle(&mut self, path: &Path) {
+        if self.contains(path) {
+            self.folders.retain(|f| f != path);
+        } else if path.to_str().is_some() {
+            // JSON can only hold UTF-8 paths
+            self.folders.push(path.to_path_buf());
+        }
+    }
+}
+
+/// Folder tree state kept between frames
+#[derive(Default)]
+pub struct FolderTreeState {
+    /// Subfolders of every folder expanded so far, read the first time it is opened
+    children: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Visible rows of the last frame, to find the folder under the pointer when files are dropped
+    drop_targets: Vec<(egui::Rect, PathBuf)>,
+    /// Directory last drawn as current, as it was given
+    shown: PathBuf,
+    /// Absolute form of `shown`, resolved only when it changes
+    current: PathBuf,
+    /// Root of the file system the current directory is on
+    fs_root: PathBuf,
+}
+
+impl FolderTreeState {
+    /// Folder row under `pos` in the last drawn frame
+    pub fn drop_target_at(&self, pos: egui::Pos2) -> Option<&PathBuf> {
+        self.drop_targets
+            .iter()
+            .find(|(rect, _)| rect.contains(pos))
+            .map(|(_, path)| path)
+    }
+
+    /// Forgets rows of the last frame, e.g. while the tree is collapsed and not drawn
+    pub fn clear_drop_targets(&mut self) {
+        self.drop_targets.clear();
+    }
+
+    /// Reads the subfolders of `dir` again the next time it is shown
+    pub fn invalidate(&mut self, dir: &Path) {
+        self.children.remove(dir);
+    }
+
+    fn subfolders(&mut self, dir: &Path) -> Vec<PathBuf> {
+        self.children
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| read_subfolders(dir))
+            .clone()
+    }
+}
+
+/// Draws the folder tree rooted at home, the favorites and the file system root.
+/// Folders load their children only when expanded, and the current directory is highlighted.
+///
+/// Returns the folder that was clicked.
+pub fn folder_tree(
+    ui: &mut egui::Ui,
+    state: &mut FolderTreeState,
+    favorites: &mut FavoriteFolders,
+    directory: &Path,
+) -> Option<PathBuf> {
+    let mut picked = None;
+    state.drop_targets.clear();
+
+    // Expand the ancestors of a newly browsed directory so it shows up in the tree
+    let mut reveal = false;
+    if state.shown != directory {
+        state.shown = directory.to_path_buf();
+        let current = std::fs::canonicalize(directory).unwrap_or_else(|_| directory.to_path_buf());
+        reveal = state.current != current;
+        state.fs_root = current
+            .ancestors()
+            .last()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        state.current = current;
+    }
+
+    ui.horizontal(|ui| {
+        let label = if favorites.contains(&state.current) {
+            "★ Unfavorite"
+        } else {
+            "☆ Favorite"
+        };
+        if ui
+            .small_button(label)
+            .on_hover_text("Add or remove the current directory as a tree root")
+            .clicked()
+        {
+            let current = state.current.clone();
+            favorites.toggle(&current);
+            favorites.save();
+        }
+        if ui.small_button("Reload").clicked() {
+            state.children.clear();
+        }
+    });
+
+    let mut roots = vec![("Home".to_string(), home_dir())];
+    roots.extend(favorites.folders.iter().map(|f| (folder_name(f), f.clone())));
+    if !state.fs_root.as_os_str().is_empty() {
+        roots.push((state.fs_root.display().to_string(), state.fs_root.clone()));
+    }
+
+    let mut unfavorite = None;
+    for (label, path) in roots {
+        let response = folder_node(ui, state, &path, label, reveal, &mut picked);
+        if favorites.contains(&path) {
+            response.context_menu(|ui| {
+                if ui.button("Remove from favorites").clicked() {
+                    unfavorite = Some(path.clone());
+                    ui.close();
+                }
+            });
+        }
+    }
+    if let Some(path) = unfavorite {
+        favorites.toggle(&path);
+        favorites.save();
+    }
+
+    picked.filter(|p| *p != state.current)
+}
+
+fn folder_node(
+    ui: &mut egui::Ui,
+    state: &mut FolderTreeState,
+    path: &Path,
+    label: String,
+    reveal: bool,
+    picked: &mut Option<PathBuf>,
+) -> egui::Response {
+    let is_current = state.current == path;
+    let id = ui.make_persistent_id(("folder_tree", path));
+    let mut collapsing =
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false);
+    if reveal && !is_current && state.current.starts_with(path) {
+        collapsing.set_open(true);
+    }
+
+    // Folders already known to have no subfolders get no expand arrow
+    let response = if state.children.get(path).is_some_and(Vec::is_empty) {
+        ui.horizontal(|ui| {
+            ui.add_space(ui.spacing().indent);
+            ui.selectable_label(is_current, label)
+        })
+        .inner
+    } else {
+        let (_, header, _) = collapsing
+            .show_header(ui, |ui| ui.selectable_label(is_current, label))
+            .body(|ui| {
+                for child in state.subfolders(path) {
+                    let name = folder_name(&child);
+                    folder_node(ui, state, &child, name, reveal, picked);
+                }
+            });
+        header.inner
+    };
+
+    if response.clicked() {
+        *picked = Some(path.to_path_buf());
+    }
+    if reveal && is_current {
+        response.scroll_to_me(None);
+    }
+    // Rows scrolled out of view must not catch drops
+    let visible = ui.clip_rect().intersect(response.rect);
+    if visible.is_positive() {
+        state.drop_targets.push((visible, path.to_path_buf()));
+    }
+
+    response.on_hover_text(path.display().to_string())
+}
+
+fn folder_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Visible subfolders of `dir` in natural order. Unreadable folders just have none.
+fn read_subfolders(dir: &Path) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read folder '{}': {}", dir.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut folders: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .map(|e| e.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    folders.sort_by(|a, b| natural_cmp(&folder_name(a), &folder_name(b)));
+    folders
+}
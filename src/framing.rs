@@ -0,0 +1,210 @@
+use bevy::{camera::primitives::Aabb, prelude::*};
+use bevy_egui::egui;
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    bounds::{ModelBounds, model_bounds},
+    config::{load_json, save_json},
+    files::{CurrentGltfEntity, OpenFile},
+    flycam::FlyCamera,
+    views::CameraPose,
+};
+
+const MODEL_CAMERAS_FILE: &str = "model_cameras.json";
+
+/// Number of models whose last camera is remembered
+const MAX_REMEMBERED: usize = 200;
+/// Room left around a framed model, as a factor of the tight fit
+const FRAME_MARGIN: f32 = 1.15;
+
+/// What the camera does when another model is opened
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum CameraOnOpen {
+    /// Stay where it is
+    #[default]
+    Keep,
+    /// Fit the new model into the view, keeping the viewing direction
+    Frame,
+    /// Go back to where the camera was when this model was last shown; frame it otherwise
+    Restore,
+}
+
+impl CameraOnOpen {
+    pub const ALL: [CameraOnOpen; 3] = [
+        CameraOnOpen::Keep,
+        CameraOnOpen::Frame,
+        CameraOnOpen::Restore,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CameraOnOpen::Keep => "Keep camera",
+            CameraOnOpen::Frame => "Frame model",
+            CameraOnOpen::Restore => "Restore last view",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct RememberedView {
+    path: PathBuf,
+    pose: CameraPose,
+}
+
+/// Camera setting for newly opened models and the last camera of each model, persisted in the
+/// config dir
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct ModelCameras {
+    #[serde(default)]
+    pub on_open: CameraOnOpen,
+    /// Most recently left model first
+    #[serde(default)]
+    last_views: Vec<RememberedView>,
+    /// Model the camera currently looks at
+    #[serde(skip)]
+    shown: PathBuf,
+    /// Set while the new model waits for its bounds to be framed
+    #[serde(skip)]
+    frame_pending: bool,
+}
+
+impl ModelCameras {
+    pub fn load() -> Self {
+        load_json(MODEL_CAMERAS_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = save_json(MODEL_CAMERAS_FILE, self) {
+            warn!("Failed to save model cameras: {}", e);
+        }
+    }
+
+    fn remember(&mut self, path: &Path, pose: CameraPose) {
+        // JSON can only hold UTF-8 paths
+        if path.as_os_str().is_empty() || path.to_str().is_none() {
+            return;
+        }
+        self.last_views.retain(|view| view.path != path);
+        self.last_views.insert(
+            0,
+            RememberedView {
+                path: path.to_path_buf(),
+                pose,
+            },
+        );
+        self.last_views.truncate(MAX_REMEMBERED);
+    }
+
+    fn last_view(&self, path: &Path) -> Option<CameraPose> {
+        self.last_views
+            .iter()
+            .find(|view| view.path == path)
+            .map(|view| view.pose)
+    }
+}
+
+/// Applies [`ModelCameras::on_open`] when another model is opened, after remembering the camera
+/// of the one left. Only the camera targets change, so the camera glides to its new place.
+pub fn switch_model_camera(
+    open_file: Res<OpenFile>,
+    fly: Res<FlyCamera>,
+    mut cameras: ResMut<ModelCameras>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    if !open_file.is_changed() || cameras.shown == open_file.0 {
+        return;
+    }
+    let (mut camera, mut projection) = camera.into_inner();
+
+    let left = std::mem::replace(&mut cameras.shown, open_file.0.clone());
+    cameras.remember(&left, CameraPose::capture(&camera, &projection));
+    cameras.frame_pending = false;
+    if !left.as_os_str().is_empty() {
+        cameras.save();
+    }
+
+    // The fly camera keeps its own position
+    if open_file.is_empty() || fly.active {
+        return;
+    }
+    match cameras.on_open {
+        CameraOnOpen::Keep => {}
+        CameraOnOpen::Frame => cameras.frame_pending = true,
+        CameraOnOpen::Restore => match cameras.last_view(&open_file.0) {
+            Some(pose) => pose.apply(&mut camera, &mut projection),
+            None => cameras.frame_pending = true,
+        },
+    }
+}
+
+/// Frames the new model as soon as its meshes have bounds
+pub fn frame_new_model(
+    mut cameras: ResMut<ModelCameras>,
+    current_gltf: Res<CurrentGltfEntity>,
+    children: Query<&Children>,
+    aabbs: Query<(&Aabb, &GlobalTransform)>,
+    camera: Single<(&mut PanOrbitCamera, &Projection)>,
+) {
+    if !cameras.frame_pending {
+        return;
+    }
+    let Some(bounds) = current_gltf
+        .0
+        .and_then(|root| model_bounds(root, &children, &aabbs))
+    else {
+        return;
+    };
+    cameras.frame_pending = false;
+    let (mut camera, projection) = camera.into_inner();
+    frame_bounds(&mut camera, projection, bounds);
+}
+
+/// Points the camera at the middle of `bounds`, far enough back for all of it to fit the view
+fn frame_bounds(camera: &mut PanOrbitCamera, projection: &Projection, bounds: ModelBounds) {
+    let (fov, aspect) = match projection {
+        Projection::Perspective(p) => (p.fov, p.aspect_ratio),
+        _ => {
+            let p = PerspectiveProjection::default();
+            (p.fov, p.aspect_ratio)
+        }
+    };
+    // The narrower of the vertical and horizontal field of view decides
+    let half_fov = (fov / 2.0).min(((fov / 2.0).tan() * aspect).atan());
+    let radius = bounds.size() / 2.0 / half_fov.sin() * FRAME_MARGIN;
+
+    camera.target_focus = bounds.center();
+    camera.target_radius = radius.max(0.01);
+    info!("Framing model at {:?}, radius {:.2}", bounds.center(), radius);
+}
+
+/// Remembers the camera of the open model when the viewer closes
+pub fn remember_camera_on_exit(
+    mut exits: MessageReader<AppExit>,
+    mut cameras: ResMut<ModelCameras>,
+    camera: Single<(&PanOrbitCamera, &Projection)>,
+) {
+    if exits.read().last().is_none() {
+        return;
+    }
+    let (camera, projection) = camera.into_inner();
+    let shown = cameras.shown.clone();
+    cameras.remember(&shown, CameraPose::capture(camera, projection));
+    cameras.save();
+}
+
+/// Menu picking [`ModelCameras::on_open`]
+pub fn camera_on_open_menu(ui: &mut egui::Ui, cameras: &mut ModelCameras) {
+    ui.menu_button("On open", |ui| {
+        for mode in CameraOnOpen::ALL {
+            let selected = cameras.on_open == mode;
+            if ui.radio(selected, mode.label()).clicked() && !selected {
+                cameras.on_open = mode;
+                cameras.save();
+            }
+        }
+    })
+    .response
+    .on_hover_text("Camera when another model is opened");
+}
@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use bevy_enhanced_input::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use std::f32::consts::FRAC_PI_2;
+
+use crate::{
+    keybindings::{KeyBindings, KeyContext, ViewerAction},
+    ui::ViewMode,
+};
+
+/// Radians per second at full stick deflection
+const ORBIT_SPEED: f32 = 2.5;
+/// Orbit radii per second at full stick deflection
+const PAN_SPEED: f32 = 1.0;
+/// Fraction of the orbit radius per second at a fully pressed trigger
+const ZOOM_SPEED: f32 = 1.5;
+const MIN_RADIUS: f32 = 0.05;
+
+/// Analog camera control: left stick orbits, right stick pans, the triggers zoom.
+/// Axes are not rebindable; the buttons live in [`KeyBindings`]. Active only in the 3D view.
+#[derive(Component)]
+pub struct GamepadCameraAction;
+
+#[derive(InputAction)]
+#[action_output(Vec2)]
+pub struct CameraOrbit;
+
+#[derive(InputAction)]
+#[action_output(Vec2)]
+pub struct CameraPan;
+
+#[derive(InputAction)]
+#[action_output(f32)]
+pub struct CameraZoomIn;
+
+#[derive(InputAction)]
+#[action_output(f32)]
+pub struct CameraZoomOut;
+
+/// Camera context, spawned on the [`crate::ui::UiKeyAction`] entity
+pub fn gamepad_camera_actions() -> impl Bundle {
+    (
+        GamepadCameraAction,
+        actions!(GamepadCameraAction[
+            (
+                Action::<CameraOrbit>::new(),
+                DeadZone::default(),
+                Bindings::spawn(Axial::left_stick()),
+            ),
+            (
+                Action::<CameraPan>::new(),
+                DeadZone::default(),
+                Bindings::spawn(Axial::right_stick()),
+            ),
+            (
+                Action::<CameraZoomIn>::new(),
+                DeadZone::default(),
+                bindings![GamepadButton::RightTrigger2],
+            ),
+            (
+                Action::<CameraZoomOut>::new(),
+                DeadZone::default(),
+                bindings![GamepadButton::LeftTrigger2],
+            ),
+        ]),
+    )
+}
+
+pub fn handle_camera_orbit(
+    trigger: On<Fire<CameraOrbit>>,
+    time: Res<Time>,
+    mut camera: Single<&mut PanOrbitCamera>,
+) {
+    let delta = trigger.value * ORBIT_SPEED * time.delta_secs();
+    camera.target_yaw -= delta.x;
+    camera.target_pitch = (camera.target_pitch + delta.y).clamp(-FRAC_PI_2, FRAC_PI_2);
+}
+
+pub fn handle_camera_pan(
+    trigger: On<Fire<CameraPan>>,
+    time: Res<Time>,
+    camera: Single<(&mut PanOrbitCamera, &Transform)>,
+) {
+    let (mut camera, transform) = camera.into_inner();
+    // Scaled by the radius so panning feels the same at any zoom
+    let step = trigger.value * PAN_SPEED * camera.target_radius * time.delta_secs();
+    let offset = transform.right() * step.x + transform.up() * step.y;
+    camera.target_focus += offset;
+}
+
+pub fn handle_camera_zoom_in(
+    trigger: On<Fire<CameraZoomIn>>,
+    time: Res<Time>,
+    mut camera: Single<&mut PanOrbitCamera>,
+) {
+    zoom(&mut camera, trigger.value * ZOOM_SPEED * time.delta_secs());
+}
+
+pub fn handle_camera_zoom_out(
+    trigger: On<Fire<CameraZoomOut>>,
+    time: Res<Time>,
+    mut camera: Single<&mut PanOrbitCamera>,
+) {
+    zoom(&mut camera, -trigger.value * ZOOM_SPEED * time.delta_secs());
+}
+
+/// Moves the camera `amount` of its radius closer to the focus, or away for negative amounts
+fn zoom(camera: &mut PanOrbitCamera, amount: f32) {
+    camera.target_radius = (camera.target_radius * (1.0 - amount)).max(MIN_RADIUS);
+}
+
+/// Short glyph text and face colour of a gamepad button, in Xbox layout
+fn glyph(button: GamepadButton) -> (&'static str, egui::Color32) {
+    let face = egui::Color32::from_gray(200);
+    match button {
+        GamepadButton::South => ("A", egui::Color32::from_rgb(96, 186, 70)),
+        GamepadButton::East => ("B", egui::Color32::from_rgb(220, 70, 60)),
+        GamepadButton::West => ("X", egui::Color32::from_rgb(60, 130, 220)),
+        GamepadButton::North => ("Y", egui::Color32::from_rgb(235, 190, 50)),
+        GamepadButton::LeftTrigger => ("LB", face),
+        GamepadButton::RightTrigger => ("RB", face),
+        GamepadButton::LeftTrigger2 => ("LT", face),
+        GamepadButton::RightTrigger2 => ("RT", face),
+        GamepadButton::LeftThumb => ("LS", face),
+        GamepadButton::RightThumb => ("RS", face),
+        GamepadButton::Select => ("View", face),
+        GamepadButton::Start => ("Menu", face),
+        GamepadButton::DPadUp => ("D-pad ↑", face),
+        GamepadButton::DPadDown => ("D-pad ↓", face),
+        GamepadButton::DPadLeft => ("D-pad ←", face),
+        GamepadButton::DPadRight => ("D-pad →", face),
+        _ => ("?", face),
+    }
+}
+
+fn glyph_chip(ui: &mut egui::Ui, text: &str, fill: egui::Color32) {
+    egui::Frame::new()
+        .fill(fill)
+        .corner_radius(8.0)
+        .inner_margin(egui::vec2(5.0, 1.0))
+        .show(ui, |ui| {
+            ui.label(egui::RichText::new(text).strong().color(egui::Color32::BLACK));
+        });
+}
+
+/// First gamepad button bound to `action`
+fn gamepad_button(bindings: &KeyBindings, action: ViewerAction) -> Option<GamepadButton> {
+    bindings
+        .get(action)
+        .into_iter()
+        .find_map(|binding| match binding {
+            Binding::GamepadButton(button) => Some(button),
+            _ => None,
+        })
+}
+
+/// Strip of button glyphs and what they do in the current view, shown along the bottom edge
+/// while a gamepad is connected
+pub fn gamepad_hints_ui(
+    mut contexts: EguiContexts,
+    gamepads: Query<(), With<Gamepad>>,
+    bindings: Res<KeyBindings>,
+    view_mode: Res<ViewMode>,
+) -> Result {
+    if gamepads.is_empty() {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+
+    // The list and the grid replace each other, like their key contexts
+    let hidden = if *view_mode == ViewMode::Grid {
+        KeyContext::List
+    } else {
+        KeyContext::Grid
+    };
+
+    egui::Area::new(egui::Id::new("gamepad_hints"))
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(8.0, -6.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if *view_mode == ViewMode::Model {
+                    for (stick, label) in [("LS", "Orbit"), ("RS", "Pan"), ("LT/RT", "Zoom")] {
+                        glyph_chip(ui, stick, egui::Color32::from_gray(200));
+                        ui.label(label);
+                        ui.add_space(8.0);
+                    }
+                }
+                for action in ViewerAction::ALL {
+                    if action.context() == hidden {
+                        continue;
+                    }
+                    let Some(button) = gamepad_button(&bindings, action) else {
+                        continue;
+                    };
+                    let (text, fill) = glyph(button);
+                    glyph_chip(ui, text, fill);
+                    ui.label(action.label());
+                    ui.add_space(8.0);
+                }
+            });
+        });
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use bevy_enhanced_input::condition::press::Press;
+use bevy_enhanced_input::prelude::*;
+
+use crate::{
+    files::{Directory, parent_dir},
+    keybindings::{KeyBindings, ViewerAction},
+};
+
+/// Keyboard navigation of the grid view; replaces [`crate::ui::UiKeyAction`] while the grid is shown
+#[derive(Component)]
+pub struct GridKeyAction;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridLeft;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridRight;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridUp;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridDown;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridPageUp;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridPageDown;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridHome;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridEnd;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridOpen;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct GridParent;
+
+/// Seconds between repeated moves while an arrow key is held
+const GRID_REPEAT_INTERVAL: f32 = 0.12;
+
+/// Grid actions, spawned on the [`crate::ui::UiKeyAction`] entity with the context inactive
+/// until the grid view is shown
+pub fn grid_actions(bindings: &KeyBindings) -> impl Bundle {
+    actions!(GridKeyAction[
+        (
+            Action::<GridLeft>::new(),
+            Pulse::new(GRID_REPEAT_INTERVAL),
+            bindings.spawn(ViewerAction::GridLeft),
+        ),
+        (
+            Action::<GridRight>::new(),
+            Pulse::new(GRID_REPEAT_INTERVAL),
+            bindings.spawn(ViewerAction::GridRight),
+        ),
+        (
+            Action::<GridUp>::new(),
+            Pulse::new(GRID_REPEAT_INTERVAL),
+            bindings.spawn(ViewerAction::GridUp),
+        ),
+        (
+            Action::<GridDown>::new(),
+            Pulse::new(GRID_REPEAT_INTERVAL),
+            bindings.spawn(ViewerAction::GridDown),
+        ),
+        (
+            Action::<GridPageUp>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::GridPageUp),
+        ),
+        (
+            Action::<GridPageDown>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::GridPageDown),
+        ),
+        (
+            Action::<GridHome>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::GridHome),
+        ),
+        (
+            Action::<GridEnd>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::GridEnd),
+        ),
+        (
+            Action::<GridOpen>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::GridOpen),
+        ),
+        (
+            Action::<GridParent>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::GridParent),
+        ),
+    ])
+}
+
+/// Cards of the grid view and the card with the keyboard focus
+#[derive(Resource, Default)]
+pub struct GridNav {
+    /// Indices into the [`crate::files::FileList`] of the entries shown as cards
+    pub files: Vec<usize>,
+    /// Position in `files` of the focused card
+    pub focus: Option<usize>,
+    /// Layout of the last drawn grid, for moving up, down and by pages
+    pub columns: usize,
+    pub visible_rows: usize,
+    /// Set when the focus moved by keyboard, so the grid scrolls it into view
+    pub scroll_to_focus: bool,
+    /// Set by Enter; the UI opens the focused card
+    pub open_focused: bool,
+    /// Whether the grid view is shown
+    pub active: bool,
+}
+
+impl GridNav {
+    /// Moves the focus by `delta` cards, stopping at the first and last card
+    fn move_focus(&mut self, delta: isize) {
+        if self.files.is_empty() {
+            return;
+        }
+        let last = self.files.len() as isize - 1;
+        let focus = match self.focus {
+            Some(focus) => (focus as isize + delta).clamp(0, last),
+            // The first key press only shows where the focus is
+            None => 0,
+        };
+        self.focus = Some(focus as usize);
+        self.scroll_to_focus = true;
+    }
+
+    fn set_focus(&mut self, focus: usize) {
+        if self.files.is_empty() {
+            return;
+        }
+        self.focus = Some(focus.min(self.files.len() - 1));
+        self.scroll_to_focus = true;
+    }
+
+    fn page(&self) -> isize {
+        (self.columns.max(1) * self.visible_rows.max(1)) as isize
+    }
+
+    /// Index into the [`crate::files::FileList`] of the focused card
+    pub fn focused_entry(&self) -> Option<usize> {
+        self.focus.and_then(|focus| self.files.get(focus).copied())
+    }
+}
+
+pub fn handle_grid_left(_trigger: On<Fire<GridLeft>>, mut grid_nav: ResMut<GridNav>) {
+    grid_nav.move_focus(-1);
+}
+
+pub fn handle_grid_right(_trigger: On<Fire<GridRight>>, mut grid_nav: ResMut<GridNav>) {
+    grid_nav.move_focus(1);
+}
+
+pub fn handle_grid_up(_trigger: On<Fire<GridUp>>, mut grid_nav: ResMut<GridNav>) {
+    let columns = grid_nav.columns.max(1) as isize;
+    grid_nav.move_focus(-columns);
+}
+
+pub fn handle_grid_down(_trigger: On<Fire<GridDown>>, mut grid_nav: ResMut<GridNav>) {
+    let columns = grid_nav.columns.max(1) as isize;
+    grid_nav.move_focus(columns);
+}
+
+pub fn handle_grid_page_up(_trigger: On<Fire<GridPageUp>>, mut grid_nav: ResMut<GridNav>) {
+    let page = grid_nav.page();
+    grid_nav.move_focus(-page);
+}
+
+pub fn handle_grid_page_down(_trigger: On<Fire<GridPageDown>>, mut grid_nav: ResMut<GridNav>) {
+    let page = grid_nav.page();
+    grid_nav.move_focus(page);
+}
+
+pub fn handle_grid_home(_trigger: On<Fire<GridHome>>, mut grid_nav: ResMut<GridNav>) {
+    grid_nav.set_focus(0);
+}
+
+pub fn handle_grid_end(_trigger: On<Fire<GridEnd>>, mut grid_nav: ResMut<GridNav>) {
+    grid_nav.set_focus(usize::MAX);
+}
+
+pub fn handle_grid_open(_trigger: On<Fire<GridOpen>>, mut grid_nav: ResMut<GridNav>) {
+    if grid_nav.focus.is_some() {
+        grid_nav.open_focused = true;
+    }
+}
+
+pub fn handle_grid_parent(_trigger: On<Fire<GridParent>>, mut directory: ResMut<Directory>) {
+    browse_parent(&mut directory);
+}
+
+fn browse_parent(directory: &mut Directory) {
+    match parent_dir(&directory.0) {
+        Some(parent) => directory.0 = parent,
+        None => warn!("Cannot navigate up from directory: {}", directory.0.display()),
+    }
+}
+
+/// Runs a grid action picked outside the grid context, e.g. from the command palette
+pub fn run_grid_action(action: ViewerAction, grid_nav: &mut GridNav, directory: &mut Directory) {
+    let columns = grid_nav.columns.max(1) as isize;
+    let page = grid_nav.page();
+    match action {
+        ViewerAction::GridLeft => grid_nav.move_focus(-1),
+        ViewerAction::GridRight => grid_nav.move_focus(1),
+        ViewerAction::GridUp => grid_nav.move_focus(-columns),
+        ViewerAction::GridDown => grid_nav.move_focus(columns),
+        ViewerAction::GridPageUp => grid_nav.move_focus(-page),
+        ViewerAction::GridPageDown => grid_nav.move_focus(page),
+        ViewerAction::GridHome => grid_nav.set_focus(0),
+        ViewerAction::GridEnd => grid_nav.set_focus(usize::MAX),
+        ViewerAction::GridOpen => {
+            if grid_nav.focus.is_some() {
+                grid_nav.open_focused = true;
+            }
+        }
+        ViewerAction::GridParent => browse_parent(directory),
+        _ => {}
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+use crate::files::{Directory, OpenFile};
+
+/// A directory visited during this session, with the view state it was left in
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub dir: PathBuf,
+    pub open_file: PathBuf,
+    pub list_scroll: f32,
+    pub grid_scroll: f32,
+}
+
+/// Back/forward navigation history of visited directories
+#[derive(Resource, Default)]
+pub struct NavHistory {
+    back: Vec<HistoryEntry>,
+    forward: Vec<HistoryEntry>,
+    /// Directory the history currently points at
+    current_dir: PathBuf,
+    /// Scroll offsets of the current directory, written by the UI every frame
+    pub list_scroll: f32,
+    pub grid_scroll: f32,
+    /// Scroll offsets the UI should apply on its next frame
+    pub pending_list_scroll: Option<f32>,
+    pub pending_grid_scroll: Option<f32>,
+}
+
+impl NavHistory {
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    fn current_entry(&self, open_file: &OpenFile) -> HistoryEntry {
+        HistoryEntry {
+            dir: self.current_dir.clone(),
+            open_file: open_file.0.clone(),
+            list_scroll: self.list_scroll,
+            grid_scroll: self.grid_scroll,
+        }
+    }
+
+    fn restore(&mut self, entry: HistoryEntry, directory: &mut Directory, open_file: &mut OpenFile) {
+        self.current_dir = entry.dir.clone();
+        self.list_scroll = entry.list_scroll;
+        self.grid_scroll = entry.grid_scroll;
+        self.pending_list_scroll = Some(entry.list_scroll);
+        self.pending_grid_scroll = Some(entry.grid_scroll);
+
+        directory.0 = entry.dir;
+        // Only reopen the file if it still exists, otherwise keep the current model
+        if entry.open_file != open_file.0 && entry.open_file.is_file() {
+            open_file.0 = entry.open_file;
+        }
+    }
+
+    /// Steps back to the previously visited directory. Returns false if there is none.
+    pub fn go_back(&mut self, directory: &mut Directory, open_file: &mut OpenFile) -> bool {
+        let Some(entry) = self.back.pop() else {
+            return false;
+        };
+        let current = self.current_entry(open_file);
+        self.forward.push(current);
+        self.restore(entry, directory, open_file);
+        true
+    }
+
+    /// Steps forward again after [`NavHistory::go_back`]. Returns false if there is nothing ahead.
+    pub fn go_forward(&mut self, directory: &mut Directory, open_file: &mut OpenFile) -> bool {
+        let Some(entry) = self.forward.pop() else {
+            return false;
+        };
+        let current = self.current_entry(open_file);
+        self.back.push(current);
+        self.restore(entry, directory, open_file);
+        true
+    }
+}
+
+/// Records every directory change that did not come from the history itself
+pub fn track_directory_history(
+    directory: Res<Directory>,
+    open_file: Res<OpenFile>,
+    mut history: ResMut<NavHistory>,
+) {
+    if !directory.is_changed() || directory.0 == history.current_dir {
+        return;
+    }
+
+    if !history.current_dir.as_os_str().is_empty() {
+        let previous = history.current_entry(&open_file);
+        history.back.push(previous);
+        history.forward.clear();
+    }
+
+    history.current_dir = directory.0.clone();
+    history.list_scroll = 0.0;
+    history.grid_scroll = 0.0;
+    history.pending_list_scroll = Some(0.0);
+    history.pending_grid_scroll = Some(0.0);
+}
@@ -0,0 +1,666 @@
+use bevy::{
+    ecs::spawn::SpawnIter,
+    input::{ButtonState, gamepad::GamepadButtonStateChangedEvent, keyboard::KeyboardInput, mouse::MouseButtonInput},
+    prelude::*,
+};
+use bevy_egui::{EguiContexts, egui};
+use bevy_enhanced_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    config::{load_json, save_json},
+    flycam::{FlyKeyAction, fly_actions},
+    views::{ViewKeyAction, view_actions},
+    gridnav::{GridKeyAction, grid_actions},
+    ui::{
+        NavKeyAction, SystemAction, UiKeyAction, nav_key_actions, system_key_actions,
+        ui_key_actions,
+    },
+};
+
+const KEY_BINDINGS_FILE: &str = "key_bindings.json";
+/// Number of bindings each action can have
+pub const BINDINGS_PER_ACTION: usize = 3;
+
+/// Input context an action belongs to. Actions of contexts that are never active at the
+/// same time may share a binding.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum KeyContext {
+    List,
+    Grid,
+    Navigation,
+    Camera,
+    Fly,
+    System,
+}
+
+impl KeyContext {
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyContext::List => "File list",
+            KeyContext::Grid => "Grid view",
+            KeyContext::Navigation => "Navigation",
+            KeyContext::Camera => "Camera",
+            KeyContext::Fly => "Fly camera",
+            KeyContext::System => "Viewer",
+        }
+    }
+
+    /// Whether both contexts can be active at once. The list and the grid replace each other,
+    /// the camera and fly contexts only work in the 3D view where the grid is hidden, and flying
+    /// replaces the camera shortcuts.
+    fn overlaps(self, other: KeyContext) -> bool {
+        const EXCLUSIVE: [(KeyContext, KeyContext); 4] = [
+            (KeyContext::List, KeyContext::Grid),
+            (KeyContext::Camera, KeyContext::Grid),
+            (KeyContext::Fly, KeyContext::Grid),
+            (KeyContext::Camera, KeyContext::Fly),
+        ];
+        !EXCLUSIVE
+            .iter()
+            .any(|&(a, b)| (a, b) == (self, other) || (b, a) == (self, other))
+    }
+}
+
+/// Every rebindable action of the viewer
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum ViewerAction {
+    FileNavUp,
+    FileNavDown,
+    GridLeft,
+    GridRight,
+    GridUp,
+    GridDown,
+    GridPageUp,
+    GridPageDown,
+    GridHome,
+    GridEnd,
+    GridOpen,
+    GridParent,
+    NavBack,
+    NavForward,
+    ViewFront,
+    ViewBack,
+    ViewLeft,
+    ViewRight,
+    ViewTop,
+    ViewBottom,
+    ToggleProjection,
+    ToggleTurntable,
+    ToggleShadows,
+    DragSun,
+    FlyForward,
+    FlyBackward,
+    FlyLeft,
+    FlyRight,
+    FlyUp,
+    FlyDown,
+    FlyBoost,
+    ToggleWireframe,
+    ToggleView,
+    ToggleFlyCamera,
+    CommandPalette,
+    ShortcutHelp,
+}
+
+impl ViewerAction {
+    /// All actions in the order the binding menu lists them
+    pub const ALL: [ViewerAction; 36] = [
+        ViewerAction::FileNavUp,
+        ViewerAction::FileNavDown,
+        ViewerAction::GridLeft,
+        ViewerAction::GridRight,
+        ViewerAction::GridUp,
+        ViewerAction::GridDown,
+        ViewerAction::GridPageUp,
+        ViewerAction::GridPageDown,
+        ViewerAction::GridHome,
+        ViewerAction::GridEnd,
+        ViewerAction::GridOpen,
+        ViewerAction::GridParent,
+        ViewerAction::NavBack,
+        ViewerAction::NavForward,
+        ViewerAction::ViewFront,
+        ViewerAction::ViewBack,
+        ViewerAction::ViewLeft,
+        ViewerAction::ViewRight,
+        ViewerAction::ViewTop,
+        ViewerAction::ViewBottom,
+        ViewerAction::ToggleProjection,
+        ViewerAction::ToggleTurntable,
+        ViewerAction::ToggleShadows,
+        ViewerAction::DragSun,
+        ViewerAction::FlyForward,
+        ViewerAction::FlyBackward,
+        ViewerAction::FlyLeft,
+        ViewerAction::FlyRight,
+        ViewerAction::FlyUp,
+        ViewerAction::FlyDown,
+        ViewerAction::FlyBoost,
+        ViewerAction::ToggleWireframe,
+        ViewerAction::ToggleView,
+        ViewerAction::ToggleFlyCamera,
+        ViewerAction::CommandPalette,
+        ViewerAction::ShortcutHelp,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ViewerAction::FileNavUp => "Previous file",
+            ViewerAction::FileNavDown => "Next file",
+            ViewerAction::GridLeft => "Move left",
+            ViewerAction::GridRight => "Move right",
+            ViewerAction::GridUp => "Move up",
+            ViewerAction::GridDown => "Move down",
+            ViewerAction::GridPageUp => "Page up",
+            ViewerAction::GridPageDown => "Page down",
+            ViewerAction::GridHome => "First card",
+            ViewerAction::GridEnd => "Last card",
+            ViewerAction::GridOpen => "Open card",
+            ViewerAction::GridParent => "Parent folder",
+            ViewerAction::NavBack => "Back",
+            ViewerAction::NavForward => "Forward",
+            ViewerAction::ToggleWireframe => "Toggle wireframe",
+            ViewerAction::ViewFront => "Front view",
+            ViewerAction::ViewBack => "Back view",
+            ViewerAction::ViewLeft => "Left view",
+            ViewerAction::ViewRight => "Right view",
+            ViewerAction::ViewTop => "Top view",
+            ViewerAction::ViewBottom => "Bottom view",
+            ViewerAction::ToggleProjection => "Perspective / orthographic",
+            ViewerAction::ToggleTurntable => "Toggle turntable",
+            ViewerAction::ToggleShadows => "Toggle shadows",
+            ViewerAction::DragSun => "Turn sun (hold, move mouse)",
+            ViewerAction::FlyForward => "Fly forward",
+            ViewerAction::FlyBackward => "Fly backward",
+            ViewerAction::FlyLeft => "Fly left",
+            ViewerAction::FlyRight => "Fly right",
+            ViewerAction::FlyUp => "Fly up",
+            ViewerAction::FlyDown => "Fly down",
+            ViewerAction::FlyBoost => "Fly faster",
+            ViewerAction::ToggleView => "Switch view",
+            ViewerAction::ToggleFlyCamera => "Toggle fly camera",
+            ViewerAction::CommandPalette => "Command palette",
+            ViewerAction::ShortcutHelp => "Keyboard shortcuts",
+        }
+    }
+
+    pub fn context(self) -> KeyContext {
+        match self {
+            ViewerAction::FileNavUp | ViewerAction::FileNavDown => KeyContext::List,
+            ViewerAction::NavBack | ViewerAction::NavForward => KeyContext::Navigation,
+            ViewerAction::ViewFront
+            | ViewerAction::ViewBack
+            | ViewerAction::ViewLeft
+            | ViewerAction::ViewRight
+            | ViewerAction::ViewTop
+            | ViewerAction::ViewBottom
+            | ViewerAction::ToggleProjection
+            | ViewerAction::ToggleTurntable
+            | ViewerAction::ToggleShadows
+            | ViewerAction::DragSun => KeyContext::Camera,
+            ViewerAction::FlyForward
+            | ViewerAction::FlyBackward
+            | ViewerAction::FlyLeft
+            | ViewerAction::FlyRight
+            | ViewerAction::FlyUp
+            | ViewerAction::FlyDown
+            | ViewerAction::FlyBoost => KeyContext::Fly,
+            ViewerAction::ToggleWireframe
+            | ViewerAction::ToggleView
+            | ViewerAction::ToggleFlyCamera
+            | ViewerAction::CommandPalette
+            | ViewerAction::ShortcutHelp => KeyContext::System,
+            _ => KeyContext::Grid,
+        }
+    }
+
+    fn default_bindings(self) -> [Binding; BINDINGS_PER_ACTION] {
+        let bindings: &[Binding] = match self {
+            ViewerAction::FileNavUp => &[
+                KeyCode::ArrowUp.into(),
+                GamepadButton::LeftTrigger.into(),
+                GamepadButton::DPadUp.into(),
+            ],
+            ViewerAction::FileNavDown => &[
+                KeyCode::ArrowDown.into(),
+                GamepadButton::RightTrigger.into(),
+                GamepadButton::DPadDown.into(),
+            ],
+            ViewerAction::GridLeft => &[KeyCode::ArrowLeft.into(), GamepadButton::DPadLeft.into()],
+            ViewerAction::GridRight => {
+                &[KeyCode::ArrowRight.into(), GamepadButton::DPadRight.into()]
+            }
+            ViewerAction::GridUp => &[KeyCode::ArrowUp.into(), GamepadButton::DPadUp.into()],
+            ViewerAction::GridDown => &[KeyCode::ArrowDown.into(), GamepadButton::DPadDown.into()],
+            ViewerAction::GridPageUp => {
+                &[KeyCode::PageUp.into(), GamepadButton::LeftTrigger.into()]
+            }
+            ViewerAction::GridPageDown => {
+                &[KeyCode::PageDown.into(), GamepadButton::RightTrigger.into()]
+            }
+            ViewerAction::GridHome => &[KeyCode::Home.into()],
+            ViewerAction::GridEnd => &[KeyCode::End.into()],
+            ViewerAction::GridOpen => &[
+                KeyCode::Enter.into(),
+                KeyCode::NumpadEnter.into(),
+                GamepadButton::South.into(),
+            ],
+            ViewerAction::GridParent => &[KeyCode::Backspace.into()],
+            ViewerAction::NavBack => &[
+                KeyCode::ArrowLeft.with_mod_keys(ModKeys::ALT),
+                MouseButton::Back.into(),
+                GamepadButton::East.into(),
+            ],
+            ViewerAction::NavForward => &[
+                KeyCode::ArrowRight.with_mod_keys(ModKeys::ALT),
+                MouseButton::Forward.into(),
+            ],
+            // Numpad layout as in common 3D tools
+            ViewerAction::ViewFront => &[KeyCode::Numpad1.into()],
+            ViewerAction::ViewBack => &[KeyCode::Numpad1.with_mod_keys(ModKeys::CONTROL)],
+            ViewerAction::ViewRight => &[KeyCode::Numpad3.into()],
+            ViewerAction::ViewLeft => &[KeyCode::Numpad3.with_mod_keys(ModKeys::CONTROL)],
+            ViewerAction::ViewTop => &[KeyCode::Numpad7.into()],
+            ViewerAction::ViewBottom => &[KeyCode::Numpad7.with_mod_keys(ModKeys::CONTROL)],
+            ViewerAction::ToggleProjection => &[KeyCode::Numpad5.into()],
+            ViewerAction::ToggleTurntable => &[KeyCode::KeyT.into()],
+            ViewerAction::ToggleShadows => &[KeyCode::KeyS.with_mod_keys(ModKeys::ALT)],
+            ViewerAction::DragSun => &[KeyCode::KeyL.into()],
+            ViewerAction::FlyForward => &[KeyCode::KeyW.into()],
+            ViewerAction::FlyBackward => &[KeyCode::KeyS.into()],
+            ViewerAction::FlyLeft => &[KeyCode::KeyA.into()],
+            ViewerAction::FlyRight => &[KeyCode::KeyD.into()],
+            ViewerAction::FlyUp => &[KeyCode::KeyE.into(), GamepadButton::RightTrigger2.into()],
+            ViewerAction::FlyDown => &[KeyCode::KeyQ.into(), GamepadButton::LeftTrigger2.into()],
+            ViewerAction::FlyBoost => &[
+                KeyCode::ShiftLeft.into(),
+                KeyCode::ShiftRight.into(),
+                GamepadButton::LeftThumb.into(),
+            ],
+            ViewerAction::ToggleWireframe => &[KeyCode::KeyR.with_mod_keys(ModKeys::ALT)],
+            ViewerAction::ToggleView => {
+                &[KeyCode::KeyV.with_mod_keys(ModKeys::ALT), GamepadButton::North.into()]
+            }
+            ViewerAction::ToggleFlyCamera => &[KeyCode::KeyF.into(), GamepadButton::West.into()],
+            ViewerAction::CommandPalette => &[KeyCode::KeyP.with_mod_keys(ModKeys::CONTROL)],
+            // "?" on US layouts
+            ViewerAction::ShortcutHelp => {
+                &[KeyCode::Slash.with_mod_keys(ModKeys::SHIFT), KeyCode::F1.into()]
+            }
+        };
+
+        let mut slots = [Binding::None; BINDINGS_PER_ACTION];
+        slots[..bindings.len()].copy_from_slice(bindings);
+        slots
+    }
+}
+
+/// Keyboard, mouse and gamepad bindings of every [`ViewerAction`], persisted in the config dir.
+/// The input contexts are rebuilt from it whenever it changes.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KeyBindings {
+    bindings: BTreeMap<ViewerAction, [Binding; BINDINGS_PER_ACTION]>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: ViewerAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn load() -> Self {
+        let mut bindings: Self = load_json(KEY_BINDINGS_FILE).unwrap_or_default();
+        // Actions added since the file was saved get their defaults
+        for action in ViewerAction::ALL {
+            bindings
+                .bindings
+                .entry(action)
+                .or_insert_with(|| action.default_bindings());
+        }
+        bindings
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = save_json(KEY_BINDINGS_FILE, self) {
+            warn!("Failed to save key bindings: {}", e);
+        }
+    }
+
+    pub fn get(&self, action: ViewerAction) -> [Binding; BINDINGS_PER_ACTION] {
+        self.bindings
+            .get(&action)
+            .copied()
+            .unwrap_or([Binding::None; BINDINGS_PER_ACTION])
+    }
+
+    /// Bindings of `action` for display, e.g. "Alt + R, F1"; empty when it has none
+    pub fn describe(&self, action: ViewerAction) -> String {
+        self.get(action)
+            .into_iter()
+            .filter(|binding| *binding != Binding::None)
+            .map(|binding| binding.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Binding entities of `action` for its `actions!` entry
+    pub fn spawn(&self, action: ViewerAction) -> impl Bundle {
+        Bindings::spawn(SpawnIter(
+            self.get(action)
+                .into_iter()
+                .filter(|binding| *binding != Binding::None),
+        ))
+    }
+
+    /// Puts `binding` into `slot` of `action`, dropping it from the action's other slots
+    fn set(&mut self, action: ViewerAction, slot: usize, binding: Binding) {
+        let slots = self
+            .bindings
+            .entry(action)
+            .or_insert([Binding::None; BINDINGS_PER_ACTION]);
+        for existing in slots.iter_mut() {
+            if *existing == binding {
+                *existing = Binding::None;
+            }
+        }
+        slots[slot] = binding;
+    }
+
+    fn clear(&mut self, action: ViewerAction, slot: usize) {
+        if let Some(slots) = self.bindings.get_mut(&action) {
+            slots[slot] = Binding::None;
+        }
+    }
+
+    /// Another action that already uses `binding` in a context active together with `action`'s
+    fn conflict(&self, action: ViewerAction, binding: Binding) -> Option<ViewerAction> {
+        self.bindings
+            .iter()
+            .filter(|(other, _)| **other != action && other.context().overlaps(action.context()))
+            .find(|(_, slots)| slots.contains(&binding))
+            .map(|(other, _)| *other)
+    }
+}
+
+/// A captured binding that is already used by another action
+struct BindingConflict {
+    action: ViewerAction,
+    slot: usize,
+    binding: Binding,
+    other: ViewerAction,
+}
+
+/// State of the key binding window
+#[derive(Resource, Default)]
+pub struct KeyBindingMenu {
+    pub open: bool,
+    /// Action and slot waiting for the next key, mouse or gamepad button press
+    capturing: Option<(ViewerAction, usize)>,
+    conflict: Option<BindingConflict>,
+}
+
+impl KeyBindingMenu {
+    /// While a binding is captured, viewer shortcuts are suspended
+    pub fn is_capturing(&self) -> bool {
+        self.capturing.is_some()
+    }
+}
+
+/// Assigns the next pressed key, mouse button or gamepad button to the slot being rebound.
+/// Ctrl, Shift, Alt and Super held at that moment become part of a key or mouse binding,
+/// Escape cancels.
+pub fn capture_binding(
+    mut menu: ResMut<KeyBindingMenu>,
+    mut bindings: ResMut<KeyBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut key_events: MessageReader<KeyboardInput>,
+    mut mouse_events: MessageReader<MouseButtonInput>,
+    mut gamepad_events: MessageReader<GamepadButtonStateChangedEvent>,
+) {
+    // Always drain, so presses from before the capture started are not picked up
+    let pressed_keys: Vec<KeyCode> = key_events
+        .read()
+        .filter(|e| e.state == ButtonState::Pressed)
+        .map(|e| e.key_code)
+        .collect();
+    let pressed_buttons: Vec<MouseButton> = mouse_events
+        .read()
+        .filter(|e| e.state == ButtonState::Pressed)
+        .map(|e| e.button)
+        .collect();
+    let pressed_gamepad: Vec<GamepadButton> = gamepad_events
+        .read()
+        .filter(|e| e.state == ButtonState::Pressed)
+        .map(|e| e.button)
+        .collect();
+
+    let Some((action, slot)) = menu.capturing else {
+        return;
+    };
+    if pressed_keys.contains(&KeyCode::Escape) {
+        info!("Cancelled binding '{}'", action.label());
+        menu.capturing = None;
+        return;
+    }
+
+    let mod_keys = held_mod_keys(&keys);
+    let Some(binding) = pressed_keys
+        .into_iter()
+        .filter(|key| !is_mod_key(*key))
+        .map(|key| key.with_mod_keys(mod_keys))
+        // Left click stays reserved for the interface
+        .chain(
+            pressed_buttons
+                .into_iter()
+                .filter(|button| *button != MouseButton::Left)
+                .map(|button| button.with_mod_keys(mod_keys)),
+        )
+        .chain(pressed_gamepad.into_iter().map(Binding::from))
+        .next()
+    else {
+        return;
+    };
+
+    menu.capturing = None;
+    match bindings.conflict(action, binding) {
+        Some(other) => {
+            info!("'{}' is already used by '{}'", binding, other.label());
+            menu.conflict = Some(BindingConflict {
+                action,
+                slot,
+                binding,
+                other,
+            });
+        }
+        None => {
+            info!("Assigning '{}' to '{}'", binding, action.label());
+            bindings.set(action, slot, binding);
+            bindings.save();
+        }
+    }
+}
+
+fn is_mod_key(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::ShiftLeft
+            | KeyCode::ShiftRight
+            | KeyCode::ControlLeft
+            | KeyCode::ControlRight
+            | KeyCode::AltLeft
+            | KeyCode::AltRight
+            | KeyCode::SuperLeft
+            | KeyCode::SuperRight
+    )
+}
+
+fn held_mod_keys(keys: &ButtonInput<KeyCode>) -> ModKeys {
+    let mut mod_keys = ModKeys::empty();
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        mod_keys |= ModKeys::SHIFT;
+    }
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        mod_keys |= ModKeys::CONTROL;
+    }
+    if keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        mod_keys |= ModKeys::ALT;
+    }
+    if keys.any_pressed([KeyCode::SuperLeft, KeyCode::SuperRight]) {
+        mod_keys |= ModKeys::SUPER;
+    }
+    mod_keys
+}
+
+/// Respawns the actions of every input context after the bindings changed
+pub fn apply_key_bindings(
+    mut commands: Commands,
+    bindings: Res<KeyBindings>,
+    ui_keys: Single<Entity, With<UiKeyAction>>,
+    system_keys: Single<Entity, With<SystemAction>>,
+) {
+    // The contexts were spawned from the loaded bindings
+    if !bindings.is_changed() || bindings.is_added() {
+        return;
+    }
+
+    commands
+        .entity(*ui_keys)
+        .despawn_related::<Actions<UiKeyAction>>()
+        .despawn_related::<Actions<NavKeyAction>>()
+        .despawn_related::<Actions<GridKeyAction>>()
+        .despawn_related::<Actions<ViewKeyAction>>()
+        .despawn_related::<Actions<FlyKeyAction>>()
+        .insert((
+            ui_key_actions(&bindings),
+            nav_key_actions(&bindings),
+            grid_actions(&bindings),
+            view_actions(&bindings),
+            fly_actions(&bindings),
+        ));
+    commands
+        .entity(*system_keys)
+        .despawn_related::<Actions<SystemAction>>()
+        .insert(system_key_actions(&bindings));
+}
+
+/// Window listing every action with its bindings. Clicking a binding waits for a new one,
+/// ✖ removes it.
+pub fn key_bindings_ui(
+    mut contexts: EguiContexts,
+    mut menu: ResMut<KeyBindingMenu>,
+    mut bindings: ResMut<KeyBindings>,
+) -> Result {
+    if !menu.open {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+
+    let mut open = true;
+    egui::Window::new("Key bindings")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("key_bindings")
+                .striped(true)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    let mut context = None;
+                    for action in ViewerAction::ALL {
+                        if context != Some(action.context()) {
+                            context = Some(action.context());
+                            ui.strong(action.context().label());
+                            ui.end_row();
+                        }
+
+                        ui.label(action.label());
+                        for (slot, binding) in bindings.get(action).into_iter().enumerate() {
+                            let capturing = menu.capturing == Some((action, slot));
+                            let text = if capturing {
+                                "Press a key…".to_string()
+                            } else if binding == Binding::None {
+                                "–".to_string()
+                            } else {
+                                binding.to_string()
+                            };
+                            ui.horizontal(|ui| {
+                                let button = egui::Button::new(text)
+                                    .selected(capturing)
+                                    .min_size(egui::vec2(130.0, 0.0));
+                                if ui.add(button).clicked() {
+                                    menu.capturing = Some((action, slot));
+                                    menu.conflict = None;
+                                }
+                                if ui
+                                    .add_enabled(
+                                        binding != Binding::None,
+                                        egui::Button::new("✖").small(),
+                                    )
+                                    .on_hover_text("Remove binding")
+                                    .clicked()
+                                {
+                                    bindings.clear(action, slot);
+                                    bindings.save();
+                                }
+                            });
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            if let Some(conflict) = &menu.conflict {
+                ui.separator();
+                ui.label(format!(
+                    "\"{}\" is already used by \"{}\".",
+                    conflict.binding,
+                    conflict.other.label()
+                ));
+                let mut resolved = false;
+                ui.horizontal(|ui| {
+                    if ui.button("Replace").clicked() {
+                        for (slot, binding) in bindings.get(conflict.other).into_iter().enumerate()
+                        {
+                            if binding == conflict.binding {
+                                bindings.clear(conflict.other, slot);
+                            }
+                        }
+                        bindings.set(conflict.action, conflict.slot, conflict.binding);
+                        bindings.save();
+                        resolved = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        resolved = true;
+                    }
+                });
+                if resolved {
+                    menu.conflict = None;
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Reset to defaults").clicked() {
+                    *bindings = KeyBindings::default();
+                    bindings.save();
+                    menu.capturing = None;
+                    menu.conflict = None;
+                }
+                ui.weak("Hold Ctrl, Shift, Alt or Super for a combination; Esc cancels.");
+            });
+        });
+
+    if !open {
+        menu.open = false;
+        menu.capturing = None;
+        menu.conflict = None;
+    }
+    Ok(())
+}
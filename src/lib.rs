@@ -1,9 +1,27 @@
+pub mod bounds;
+pub mod config;
+pub mod contextmenu;
 pub mod files;
+pub mod flycam;
+pub mod foldertree;
+pub mod framing;
+pub mod gamepad;
+pub mod gridnav;
+pub mod history;
+pub mod keybindings;
+pub mod lighting;
+pub mod pathbar;
 pub mod style;
 pub mod ui;
 pub mod envlight;
 pub mod objects;
+pub mod palette;
+pub mod recent;
+pub mod selection;
+pub mod stats;
 pub mod thumbnails;
+pub mod turntable;
+pub mod views;
 
 
 
@@ -0,0 +1,851 @@
+use bevy::{
+    camera::{primitives::Aabb, visibility::RenderLayers},
+    input::mouse::MouseMotion,
+    light::CascadeShadowConfigBuilder,
+    prelude::*,
+};
+use bevy_egui::{EguiContexts, egui};
+use bevy_enhanced_input::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+use std::{f32::consts::PI, fs};
+
+use crate::{
+    bounds::{ModelBounds, model_bounds},
+    config::{config_dir, load_json, save_json},
+    envlight::{EnvironmentMaps, EnvironmentSettings, environment_ui},
+    files::CurrentGltfEntity,
+    keybindings::{KeyBindings, ViewerAction},
+};
+
+const LIGHTING_FILE: &str = "lighting.json";
+/// Folder in the config dir holding one JSON file per saved light rig
+const LIGHT_RIGS_DIR: &str = "light_rigs";
+
+/// Model size assumed while no model is loaded
+const DEFAULT_MODEL_SIZE: f32 = 5.0;
+/// Degrees the sun turns per pixel of mouse movement while dragged
+const DRAG_SENSITIVITY: f32 = 0.25;
+/// Keeps the sun off the poles, where its direction has no defined up
+const MAX_ELEVATION: f32 = 89.0;
+
+/// Marks the main scene's directional light
+#[derive(Component)]
+pub struct Sun;
+
+/// Marks a light spawned for [`LightRig::lights`], with its position in the rig and its kind
+#[derive(Component)]
+pub struct RigLightEntity {
+    index: usize,
+    kind: LightKind,
+}
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct DragSun;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleShadows;
+
+/// Unit vector pointing from the scene towards a light at `azimuth` and `elevation` degrees
+fn direction(azimuth: f32, elevation: f32) -> Vec3 {
+    let (azimuth, elevation) = (azimuth.to_radians(), elevation.to_radians());
+    Vec3::new(
+        elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+        elevation.cos() * azimuth.cos(),
+    )
+}
+
+fn color([r, g, b]: [f32; 3]) -> Color {
+    Color::srgb(r, g, b)
+}
+
+/// Directional light settings. Sizes and distances are relative to the model, so they work
+/// for tiny and huge models alike.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SunSettings {
+    /// Compass direction the light comes from, in degrees around the vertical axis
+    pub azimuth: f32,
+    /// Height of the light above the horizon, in degrees
+    pub elevation: f32,
+    /// Lux
+    pub illuminance: f32,
+    /// sRGB colour
+    pub color: [f32; 3],
+    pub shadows: bool,
+    /// Percentage-closer soft shadows, blurring with distance from the caster
+    pub soft_shadows: bool,
+    /// Light size for soft shadows, as a fraction of the model size
+    pub soft_shadow_size: f32,
+    pub cascades: usize,
+    /// Distance up to which shadows are drawn, in model sizes
+    pub shadow_distance: f32,
+    /// Far end of the first cascade, as a fraction of the shadow distance
+    pub first_cascade: f32,
+}
+
+impl Default for SunSettings {
+    fn default() -> Self {
+        Self {
+            azimuth: 90.0,
+            elevation: 45.0,
+            illuminance: 6_000.0,
+            color: [1.0, 1.0, 1.0],
+            shadows: true,
+            soft_shadows: false,
+            soft_shadow_size: 0.05,
+            cascades: 4,
+            shadow_distance: 5.0,
+            first_cascade: 0.25,
+        }
+    }
+}
+
+impl SunSettings {
+    /// Unit vector pointing from the scene towards the light
+    pub fn direction(&self) -> Vec3 {
+        direction(self.azimuth, self.elevation)
+    }
+
+    /// Cascades covering `shadow_distance` model sizes
+    fn cascades(&self, model_size: f32) -> CascadeShadowConfig {
+        let maximum_distance = self.shadow_distance * model_size;
+        CascadeShadowConfigBuilder {
+            num_cascades: self.cascades.max(1),
+            // Small models need shadows closer than the default minimum
+            minimum_distance: 0.0,
+            maximum_distance,
+            first_cascade_far_bound: maximum_distance * self.first_cascade,
+            ..default()
+        }
+        .build()
+    }
+
+    /// Sets up `light` for a model of `model_size`, returning its transform and cascades
+    fn apply(
+        &self,
+        model_size: f32,
+        light: &mut DirectionalLight,
+    ) -> (Transform, CascadeShadowConfig) {
+        light.color = color(self.color);
+        light.illuminance = self.illuminance;
+        light.shadows_enabled = self.shadows;
+        light.soft_shadow_size = self
+            .soft_shadows
+            .then_some(self.soft_shadow_size * model_size);
+
+        let transform = Transform::default().looking_to(-self.direction(), Vec3::Y);
+        (transform, self.cascades(model_size))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LightKind {
+    #[default]
+    Directional,
+    Point,
+    Spot,
+}
+
+impl LightKind {
+    pub const ALL: [LightKind; 3] = [LightKind::Directional, LightKind::Point, LightKind::Spot];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LightKind::Directional => "Directional",
+            LightKind::Point => "Point",
+            LightKind::Spot => "Spot",
+        }
+    }
+}
+
+/// Light of a rig besides the sun, placed around the model's centre
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RigLight {
+    pub kind: LightKind,
+    /// Degrees around the vertical axis
+    pub azimuth: f32,
+    /// Degrees above the horizon
+    pub elevation: f32,
+    /// Distance of point and spot lights from the model centre, in model sizes
+    pub distance: f32,
+    /// Lux reaching the model centre, so point and spot lights look the same at any model size
+    pub illuminance: f32,
+    /// sRGB colour
+    pub color: [f32; 3],
+    pub shadows: bool,
+    /// Full cone angle of spot lights, in degrees
+    pub spot_angle: f32,
+}
+
+impl Default for RigLight {
+    fn default() -> Self {
+        Self {
+            kind: LightKind::Point,
+            azimuth: 0.0,
+            elevation: 30.0,
+            distance: 2.0,
+            illuminance: 2_000.0,
+            color: [1.0, 1.0, 1.0],
+            shadows: false,
+            spot_angle: 40.0,
+        }
+    }
+}
+
+impl RigLight {
+    /// Inserts the light components for this light, replacing those of an earlier update
+    fn insert(&self, light: &mut EntityCommands, bounds: ModelBounds, sun: &SunSettings) {
+        let size = bounds.size().max(0.01);
+        let distance = self.distance * size;
+        let towards = direction(self.azimuth, self.elevation);
+        let transform = Transform::from_translation(bounds.center() + towards * distance)
+            .looking_at(bounds.center(), Vec3::Y);
+        // Lumens giving `illuminance` lux at the model centre
+        let intensity = self.illuminance * 4.0 * PI * distance * distance;
+        let range = distance * 4.0;
+        let near_z = (distance * 0.01).min(PointLight::DEFAULT_SHADOW_MAP_NEAR_Z);
+
+        match self.kind {
+            LightKind::Directional => light.insert((
+                DirectionalLight {
+                    color: color(self.color),
+                    illuminance: self.illuminance,
+                    shadows_enabled: self.shadows,
+                    ..default()
+                },
+                Transform::default().looking_to(-towards, Vec3::Y),
+                sun.cascades(size),
+            )),
+            LightKind::Point => light.insert((
+                PointLight {
+                    color: color(self.color),
+                    intensity,
+                    range,
+                    shadows_enabled: self.shadows,
+                    shadow_map_near_z: near_z,
+                    ..default()
+                },
+                transform,
+            )),
+            LightKind::Spot => {
+                let outer_angle = (self.spot_angle / 2.0).to_radians();
+                light.insert((
+                    SpotLight {
+                        color: color(self.color),
+                        intensity,
+                        range,
+                        shadows_enabled: self.shadows,
+                        shadow_map_near_z: near_z,
+                        outer_angle,
+                        inner_angle: outer_angle * 0.8,
+                        ..default()
+                    },
+                    transform,
+                ))
+            }
+        };
+    }
+}
+
+/// Built-in light rigs
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LightPreset {
+    Studio,
+    Overcast,
+    Sunset,
+    Flat,
+}
+
+impl LightPreset {
+    pub const ALL: [LightPreset; 4] = [
+        LightPreset::Studio,
+        LightPreset::Overcast,
+        LightPreset::Sunset,
+        LightPreset::Flat,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LightPreset::Studio => "Studio three-point",
+            LightPreset::Overcast => "Overcast",
+            LightPreset::Sunset => "Sunset",
+            LightPreset::Flat => "Flat",
+        }
+    }
+
+    pub fn rig(self) -> LightRig {
+        match self {
+            // Warm key with soft shadows, cool fill from the other side and a rim light behind
+            LightPreset::Studio => LightRig {
+                ambient: 150.0,
+                ambient_color: [1.0, 1.0, 1.0],
+                sun: SunSettings {
+                    azimuth: 45.0,
+                    elevation: 35.0,
+                    illuminance: 8_000.0,
+                    color: [1.0, 0.96, 0.9],
+                    soft_shadows: true,
+                    soft_shadow_size: 0.03,
+                    ..default()
+                },
+                lights: vec![
+                    RigLight {
+                        kind: LightKind::Point,
+                        azimuth: -60.0,
+                        elevation: 15.0,
+                        illuminance: 2_500.0,
+                        color: [0.88, 0.94, 1.0],
+                        ..default()
+                    },
+                    RigLight {
+                        kind: LightKind::Spot,
+                        azimuth: 180.0,
+                        elevation: 40.0,
+                        illuminance: 5_000.0,
+                        spot_angle: 35.0,
+                        ..default()
+                    },
+                ],
+            },
+            // Sun high behind clouds: bright sky, very soft shadows
+            LightPreset::Overcast => LightRig {
+                ambient: 1_000.0,
+                ambient_color: [0.86, 0.9, 1.0],
+                sun: SunSettings {
+                    azimuth: 20.0,
+                    elevation: 75.0,
+                    illuminance: 2_500.0,
+                    color: [0.92, 0.95, 1.0],
+                    soft_shadows: true,
+                    soft_shadow_size: 0.3,
+                    ..default()
+                },
+                lights: Vec::new(),
+            },
+            // Low orange sun with long shadows and blue light from the sky opposite
+            LightPreset::Sunset => LightRig {
+                ambient: 300.0,
+                ambient_color: [0.45, 0.5, 0.8],
+                sun: SunSettings {
+                    azimuth: 250.0,
+                    elevation: 8.0,
+                    illuminance: 10_000.0,
+                    color: [1.0, 0.62, 0.35],
+                    soft_shadows: true,
+                    soft_shadow_size: 0.05,
+                    shadow_distance: 8.0,
+                    ..default()
+                },
+                lights: vec![RigLight {
+                    kind: LightKind::Directional,
+                    azimuth: 70.0,
+                    elevation: 20.0,
+                    illuminance: 800.0,
+                    color: [0.5, 0.55, 0.9],
+                    ..default()
+                }],
+            },
+            // Even ambient light only, for judging colours and silhouettes
+            LightPreset::Flat => LightRig {
+                ambient: 2_000.0,
+                ambient_color: [1.0, 1.0, 1.0],
+                sun: SunSettings {
+                    illuminance: 0.0,
+                    shadows: false,
+                    ..default()
+                },
+                lights: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Ambient light, the sun and any further lights of the 3D view
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LightRig {
+    /// Ambient brightness in cd/m²
+    pub ambient: f32,
+    /// sRGB colour
+    pub ambient_color: [f32; 3],
+    pub sun: SunSettings,
+    pub lights: Vec<RigLight>,
+}
+
+impl Default for LightRig {
+    fn default() -> Self {
+        Self {
+            ambient: 0.0,
+            ambient_color: [1.0, 1.0, 1.0],
+            sun: SunSettings::default(),
+            lights: Vec::new(),
+        }
+    }
+}
+
+/// Config file of the rig called `name`, or `None` if the name cannot be a file name
+fn rig_file(name: &str) -> Option<String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|']);
+    valid.then(|| format!("{LIGHT_RIGS_DIR}/{name}.json"))
+}
+
+/// Names of the rigs saved in the config dir, sorted
+pub fn saved_rigs() -> Vec<String> {
+    let Some(dir) = config_dir().map(|d| d.join(LIGHT_RIGS_DIR)) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+pub fn save_rig(name: &str, rig: &LightRig) {
+    let Some(file) = rig_file(name) else {
+        warn!("Cannot save a light rig named '{}'", name);
+        return;
+    };
+    match save_json(&file, rig) {
+        Ok(()) => info!("Saved light rig '{}'", name),
+        Err(e) => warn!("Failed to save light rig '{}': {}", name, e),
+    }
+}
+
+pub fn load_rig(name: &str) -> Option<LightRig> {
+    rig_file(name).and_then(|file| load_json(&file))
+}
+
+pub fn delete_rig(name: &str) {
+    let Some(path) = config_dir().zip(rig_file(name)).map(|(dir, file)| dir.join(file)) else {
+        return;
+    };
+    if let Err(e) = fs::remove_file(&path) {
+        warn!("Failed to delete '{}': {}", path.display(), e);
+    }
+}
+
+/// Lighting of the 3D view and the state of its panel, persisted in the config dir
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct Lighting {
+    /// Whether the lighting panel is shown
+    #[serde(skip)]
+    pub open: bool,
+    #[serde(flatten)]
+    pub rig: LightRig,
+    /// Kept apart from the rig so presets and saved rigs leave the environment map alone
+    #[serde(default)]
+    pub environment: EnvironmentSettings,
+}
+
+impl Lighting {
+    pub fn load() -> Self {
+        load_json(LIGHTING_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = save_json(LIGHTING_FILE, self) {
+            warn!("Failed to save lighting: {}", e);
+        }
+    }
+}
+
+pub fn handle_toggle_shadows(_trigger: On<Fire<ToggleShadows>>, mut lighting: ResMut<Lighting>) {
+    lighting.rig.sun.shadows = !lighting.rig.sun.shadows;
+    lighting.save();
+}
+
+/// Sets up the ambient light, the sun and the rig lights from [`Lighting`], and again once a
+/// new model has bounds to place and scale them by
+pub fn apply_lighting(
+    mut commands: Commands,
+    lighting: Res<Lighting>,
+    mut ambient: ResMut<AmbientLight>,
+    current_gltf: Res<CurrentGltfEntity>,
+    children: Query<&Children>,
+    aabbs: Query<(&Aabb, &GlobalTransform)>,
+    // Model the bounds were measured for, and the bounds once known
+    mut measured: Local<(Option<Entity>, Option<ModelBounds>)>,
+    // Rig last applied, so changes to other settings leave the lights be
+    mut applied: Local<Option<LightRig>>,
+    sun: Single<(&mut DirectionalLight, &mut Transform, &mut CascadeShadowConfig), With<Sun>>,
+    rig_lights: Query<(Entity, &RigLightEntity)>,
+) {
+    let mut resized = false;
+    if measured.0 != current_gltf.0 || (measured.1.is_none() && current_gltf.0.is_some()) {
+        let bounds = current_gltf
+            .0
+            .and_then(|root| model_bounds(root, &children, &aabbs));
+        resized = measured.0 != current_gltf.0 || bounds.is_some();
+        *measured = (current_gltf.0, bounds);
+    }
+    if !resized && (!lighting.is_changed() || applied.as_ref() == Some(&lighting.rig)) {
+        return;
+    }
+    *applied = Some(lighting.rig.clone());
+
+    let bounds = measured.1.unwrap_or(ModelBounds {
+        min: Vec3::splat(-DEFAULT_MODEL_SIZE / 2.0),
+        max: Vec3::splat(DEFAULT_MODEL_SIZE / 2.0),
+    });
+    let rig = &lighting.rig;
+    let (mut light, mut transform, mut cascades) = sun.into_inner();
+    (*transform, *cascades) = rig.sun.apply(bounds.size().max(0.01), &mut light);
+
+    ambient.brightness = rig.ambient;
+    ambient.color = color(rig.ambient_color);
+
+    // Lights are updated in place while the rig keeps its shape, so shadow maps stay allocated
+    let mut spawned: Vec<_> = rig_lights.iter().collect();
+    spawned.sort_by_key(|(_, light)| light.index);
+    let same_shape = spawned.len() == rig.lights.len()
+        && spawned
+            .iter()
+            .zip(&rig.lights)
+            .all(|((_, existing), light)| existing.kind == light.kind);
+    if same_shape {
+        for ((entity, _), rig_light) in spawned.into_iter().zip(&rig.lights) {
+            rig_light.insert(&mut commands.entity(entity), bounds, &rig.sun);
+        }
+        return;
+    }
+
+    for (entity, _) in spawned {
+        commands.entity(entity).despawn();
+    }
+    for (index, rig_light) in rig.lights.iter().enumerate() {
+        // Like the sun, rig lights stay out of the thumbnail renders
+        let mut light = commands.spawn((
+            RigLightEntity {
+                index,
+                kind: rig_light.kind,
+            },
+            RenderLayers::layer(0),
+        ));
+        rig_light.insert(&mut light, bounds, &rig.sun);
+    }
+}
+
+/// Turns the sun with the mouse while the drag key is held, showing its direction as an arrow
+/// through the camera focus
+pub fn drag_sun(
+    drag: Single<&Action<DragSun>>,
+    mut motion: MessageReader<MouseMotion>,
+    mut lighting: ResMut<Lighting>,
+    camera: Single<&PanOrbitCamera>,
+    mut gizmos: Gizmos,
+    mut was_dragging: Local<bool>,
+) {
+    let delta: Vec2 = motion.read().map(|m| m.delta).sum();
+    if !**drag {
+        if *was_dragging {
+            *was_dragging = false;
+            lighting.save();
+        }
+        return;
+    }
+    *was_dragging = true;
+
+    if delta != Vec2::ZERO {
+        let sun = &mut lighting.rig.sun;
+        sun.azimuth = (sun.azimuth - delta.x * DRAG_SENSITIVITY).rem_euclid(360.0);
+        sun.elevation =
+            (sun.elevation - delta.y * DRAG_SENSITIVITY).clamp(-MAX_ELEVATION, MAX_ELEVATION);
+    }
+
+    let focus = camera.target_focus;
+    let start = focus + lighting.rig.sun.direction() * camera.target_radius * 0.5;
+    gizmos.arrow(start, focus, Color::srgb(1.0, 0.85, 0.3));
+}
+
+/// Azimuth and elevation fields of a light
+fn direction_edit(ui: &mut egui::Ui, azimuth: &mut f32, elevation: &mut f32) {
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::DragValue::new(azimuth)
+                .range(0.0..=360.0)
+                .suffix("°")
+                .prefix("azimuth "),
+        );
+        ui.add(
+            egui::DragValue::new(elevation)
+                .range(-MAX_ELEVATION..=MAX_ELEVATION)
+                .suffix("°")
+                .prefix("elevation "),
+        );
+    });
+}
+
+fn illuminance_slider(ui: &mut egui::Ui, illuminance: &mut f32) {
+    ui.add(
+        egui::Slider::new(illuminance, 0.0..=120_000.0)
+            .logarithmic(true)
+            .suffix(" lx"),
+    );
+}
+
+fn sun_grid(ui: &mut egui::Ui, sun: &mut SunSettings) {
+    egui::Grid::new("sun_settings")
+        .num_columns(2)
+        .spacing([12.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("Direction");
+            direction_edit(ui, &mut sun.azimuth, &mut sun.elevation);
+            ui.end_row();
+
+            ui.label("Illuminance");
+            illuminance_slider(ui, &mut sun.illuminance);
+            ui.end_row();
+
+            ui.label("Colour");
+            ui.color_edit_button_rgb(&mut sun.color);
+            ui.end_row();
+
+            ui.label("Shadows");
+            ui.checkbox(&mut sun.shadows, "");
+            ui.end_row();
+
+            ui.label("Soft shadows");
+            ui.add_enabled_ui(sun.shadows, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut sun.soft_shadows, "");
+                    ui.add_enabled(
+                        sun.soft_shadows,
+                        egui::Slider::new(&mut sun.soft_shadow_size, 0.001..=0.5)
+                            .logarithmic(true)
+                            .text("light size"),
+                    )
+                    .on_hover_text("Fraction of the model size");
+                });
+            });
+            ui.end_row();
+
+            ui.label("Cascades");
+            ui.add_enabled(sun.shadows, egui::Slider::new(&mut sun.cascades, 1..=4));
+            ui.end_row();
+
+            ui.label("Shadow distance");
+            ui.add_enabled(
+                sun.shadows,
+                egui::Slider::new(&mut sun.shadow_distance, 1.0..=50.0)
+                    .logarithmic(true)
+                    .suffix("× model"),
+            );
+            ui.end_row();
+
+            ui.label("First cascade");
+            ui.add_enabled(
+                sun.shadows && sun.cascades > 1,
+                egui::Slider::new(&mut sun.first_cascade, 0.02..=0.9).text("of distance"),
+            );
+            ui.end_row();
+        });
+}
+
+/// Settings of one rig light
+fn rig_light_grid(ui: &mut egui::Ui, index: usize, light: &mut RigLight) {
+    egui::Grid::new(("rig_light", index))
+        .num_columns(2)
+        .spacing([12.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("Type");
+            ui.horizontal(|ui| {
+                for kind in LightKind::ALL {
+                    ui.radio_value(&mut light.kind, kind, kind.label());
+                }
+            });
+            ui.end_row();
+
+            ui.label("Direction");
+            direction_edit(ui, &mut light.azimuth, &mut light.elevation);
+            ui.end_row();
+
+            if light.kind != LightKind::Directional {
+                ui.label("Distance");
+                ui.add(
+                    egui::Slider::new(&mut light.distance, 0.6..=10.0)
+                        .logarithmic(true)
+                        .suffix("× model"),
+                );
+                ui.end_row();
+            }
+
+            ui.label("Illuminance");
+            illuminance_slider(ui, &mut light.illuminance);
+            ui.end_row();
+
+            ui.label("Colour");
+            ui.color_edit_button_rgb(&mut light.color);
+            ui.end_row();
+
+            ui.label("Shadows");
+            ui.checkbox(&mut light.shadows, "");
+            ui.end_row();
+
+            if light.kind == LightKind::Spot {
+                ui.label("Cone");
+                ui.add(egui::Slider::new(&mut light.spot_angle, 5.0..=170.0).suffix("°"));
+                ui.end_row();
+            }
+        });
+}
+
+/// Window with the light rig: presets, saved rigs, ambient light, the sun and further lights
+pub fn lighting_ui(
+    mut contexts: EguiContexts,
+    mut lighting: ResMut<Lighting>,
+    mut maps: ResMut<EnvironmentMaps>,
+    bindings: Res<KeyBindings>,
+    // Name typed for saving the rig
+    mut rig_name: Local<String>,
+    // Saved rigs, read when their menu opens
+    mut rig_names: Local<Option<Vec<String>>>,
+    // Edits applied but not written yet, held back while a slider or colour is being dragged
+    mut unsaved: Local<bool>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    if *unsaved && !ctx.input(|i| i.pointer.any_down()) {
+        *unsaved = false;
+        lighting.save();
+    }
+    if !lighting.open {
+        return Ok(());
+    }
+
+    let mut open = true;
+    let mut rig = lighting.rig.clone();
+    let mut environment = lighting.environment.clone();
+    egui::Window::new("Lighting")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.menu_button("Presets", |ui| {
+                    for preset in LightPreset::ALL {
+                        if ui.button(preset.label()).clicked() {
+                            rig = preset.rig();
+                            ui.close();
+                        }
+                    }
+                });
+                let menu = ui.menu_button("Saved rigs", |ui| {
+                    let names = rig_names.get_or_insert_with(saved_rigs).clone();
+                    if names.is_empty() {
+                        ui.weak("No saved rigs");
+                    }
+                    for name in names {
+                        ui.horizontal(|ui| {
+                            if ui.button(&name).clicked() {
+                                match load_rig(&name) {
+                                    Some(loaded) => {
+                                        rig = loaded;
+                                        *rig_name = name.clone();
+                                    }
+                                    None => warn!("Could not load light rig '{}'", name),
+                                }
+                                ui.close();
+                            }
+                            if ui.small_button("✖").on_hover_text("Delete rig").clicked() {
+                                delete_rig(&name);
+                                *rig_names = None;
+                            }
+                        });
+                    }
+                });
+                if menu.inner.is_none() {
+                    *rig_names = None;
+                }
+                ui.separator();
+                ui.add(
+                    egui::TextEdit::singleline(&mut *rig_name)
+                        .hint_text("Rig name")
+                        .desired_width(110.0),
+                );
+                let name = rig_name.trim();
+                let valid = rig_file(name).is_some();
+                if ui.add_enabled(valid, egui::Button::new("Save")).clicked() {
+                    save_rig(name, &rig);
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(520.0).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Ambient");
+                    ui.add(
+                        egui::Slider::new(&mut rig.ambient, 0.0..=5_000.0)
+                            .logarithmic(true)
+                            .suffix(" cd/m²"),
+                    );
+                    ui.color_edit_button_rgb(&mut rig.ambient_color);
+                });
+
+                egui::CollapsingHeader::new("Sun")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        sun_grid(ui, &mut rig.sun);
+                        let drag_keys = bindings.describe(ViewerAction::DragSun);
+                        if !drag_keys.is_empty() {
+                            ui.weak(format!("Hold {drag_keys} and move the mouse to turn the sun."));
+                        }
+                    });
+
+                let mut remove = None;
+                for (index, light) in rig.lights.iter_mut().enumerate() {
+                    let title = format!("{} light {}", light.kind.label(), index + 1);
+                    egui::CollapsingHeader::new(title)
+                        .id_salt(("rig_light", index))
+                        .show(ui, |ui| {
+                            rig_light_grid(ui, index, light);
+                            if ui.button("Remove light").clicked() {
+                                remove = Some(index);
+                            }
+                        });
+                }
+                if let Some(index) = remove {
+                    rig.lights.remove(index);
+                }
+
+                ui.menu_button("Add light", |ui| {
+                    for kind in LightKind::ALL {
+                        if ui.button(kind.label()).clicked() {
+                            rig.lights.push(RigLight { kind, ..default() });
+                            ui.close();
+                        }
+                    }
+                });
+
+                ui.separator();
+                egui::CollapsingHeader::new("Environment")
+                    .default_open(environment.path.is_some())
+                    .show(ui, |ui| {
+                        // Only the loader reacts to the dialog and errors set here
+                        environment_ui(ui, &mut environment, maps.bypass_change_detection());
+                    });
+            });
+        });
+
+    if rig != lighting.rig || environment != lighting.environment {
+        lighting.rig = rig;
+        lighting.environment = environment;
+        *unsaved = true;
+    }
+    if !open {
+        lighting.open = false;
+    }
+    Ok(())
+}
@@ -6,27 +6,65 @@ use bevy_egui::{
     PrimaryEguiContext,
 };
 use bevy_enhanced_input::prelude::*;
-use bevy_enhanced_input::condition::press::Press;
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 use bevy::pbr::wireframe::{WireframeConfig, WireframePlugin};
-use std::{env, f32::consts::PI};
+use std::{env, f32::consts::PI, path::PathBuf};
 use view3d::{
     files::{
-        CurrentGltfEntity, Directory, EditFileName, FileList, ModelInfo, OpenFile,
+        CurrentGltfEntity, DirListing, Directory, EditFileName, FileList, ModelInfo, OpenFile,
         ShowEditFileName, SortMode, check_dir_changed, check_model_loaded, check_open_file_changed,
-        home_dir, dir_list_approved_files,
+        home_dir, poll_dir_listing,
+    },
+    flycam::{
+        FlyCamera, FlyKeyAction, apply_fly_mode, handle_fly_down, handle_fly_exit,
+        handle_fly_look, handle_fly_move, handle_fly_up, handle_toggle_fly_camera,
+    },
+    foldertree::FavoriteFolders,
+    gamepad::{
+        GamepadCameraAction, gamepad_hints_ui, handle_camera_orbit, handle_camera_pan,
+        handle_camera_zoom_in, handle_camera_zoom_out,
+    },
+    gridnav::{
+        GridKeyAction, GridNav, handle_grid_down, handle_grid_end, handle_grid_home,
+        handle_grid_left, handle_grid_open, handle_grid_page_down, handle_grid_page_up,
+        handle_grid_parent, handle_grid_right, handle_grid_up,
+    },
+    envlight::{EnvironmentMaps, apply_environment_map, load_environment_map},
+    framing::{ModelCameras, frame_new_model, remember_camera_on_exit, switch_model_camera},
+    lighting::{Lighting, Sun, apply_lighting, drag_sun, handle_toggle_shadows, lighting_ui},
+    history::{NavHistory, track_directory_history},
+    keybindings::{
+        KeyBindingMenu, KeyBindings, apply_key_bindings, capture_binding, key_bindings_ui,
     },
     objects::{EnvironmentMaterial, change_material},
-    ui::{UiKeyAction, handle_file_nav_down, handle_file_nav_up, setup_ui, ui_system},
+    palette::{
+        CommandPalette, RunAction, ShortcutHelp, command_palette_ui, run_palette_actions,
+        shortcut_help_ui, toggle_command_palette, toggle_shortcut_help,
+    },
+    recent::{RecentFiles, track_recent_files},
+    selection::{Selection, clear_selection_on_dir_change},
+    stats::{ModelStatsCache, save_model_stats},
+    ui::{
+        NavKeyAction, SystemAction, ToggleWireframe, UiKeyAction, ViewMode, handle_file_nav_down,
+        handle_file_nav_up, handle_nav_back, handle_nav_forward, handle_toggle_view, setup_ui,
+        ui_system, update_key_contexts,
+    },
+    views::{
+        CameraBookmarks, ViewKeyAction, camera_views_ui, handle_toggle_projection,
+        handle_view_back, handle_view_bottom, handle_view_front, handle_view_left,
+        handle_view_right, handle_view_top, load_camera_bookmarks,
+    },
+    turntable::{Turntable, handle_toggle_turntable, spin_turntable},
     thumbnails::{ThumbnailCache, ThumbnailQueue, GenerateThumbnail, handle_thumbnail_requests, process_thumbnail_queue, cleanup_thumbnail_cameras},
 };
 
 
 fn main() {
-    let args = env::args();
+    // args_os so a non-UTF-8 directory argument does not panic
+    let args = env::args_os();
     println!("{:?}", args);
     let dir = if args.len() > 1 {
-        Directory(args.last().unwrap_or(".".to_string()))
+        Directory(args.last().map(PathBuf::from).unwrap_or_else(|| PathBuf::from(".")))
     } else {
         let home = home_dir();
         Directory(home)
@@ -48,9 +86,29 @@ fn main() {
         .init_resource::<EditFileName>()
         .init_resource::<ShowEditFileName>()
         .insert_resource(SortMode::Name)
+        .init_resource::<FileList>()
+        .init_resource::<DirListing>()
         .init_resource::<ThumbnailCache>()
         .init_resource::<ThumbnailQueue>()
+        .init_resource::<NavHistory>()
+        .init_resource::<Selection>()
+        .init_resource::<GridNav>()
+        .init_resource::<ViewMode>()
+        .init_resource::<FlyCamera>()
+        .init_resource::<CameraBookmarks>()
+        .insert_resource(ModelCameras::load())
+        .insert_resource(Turntable::load())
+        .insert_resource(Lighting::load())
+        .init_resource::<EnvironmentMaps>()
+        .insert_resource(RecentFiles::load())
+        .insert_resource(FavoriteFolders::load())
+        .insert_resource(ModelStatsCache::load())
+        .insert_resource(KeyBindings::load())
+        .init_resource::<KeyBindingMenu>()
+        .init_resource::<CommandPalette>()
+        .init_resource::<ShortcutHelp>()
         .add_message::<GenerateThumbnail>()
+        .add_message::<RunAction>()
         //plugins
         .add_plugins(DefaultPlugins.set(AssetPlugin {
             unapproved_path_mode: bevy::asset::UnapprovedPathMode::Allow,
@@ -66,66 +124,102 @@ fn main() {
         // systems
         .add_systems(Startup, setup_scene)
         .add_systems(Startup, setup_ui)
-        .add_systems(EguiPrimaryContextPass, ui_system)
-        .add_systems(Update, check_dir_changed)
-        .add_systems(Update, check_open_file_changed)
+        .add_systems(
+            EguiPrimaryContextPass,
+            (
+                ui_system,
+                gamepad_hints_ui,
+                camera_views_ui,
+                lighting_ui,
+                key_bindings_ui,
+                shortcut_help_ui,
+                command_palette_ui,
+            )
+                .chain(),
+        )
+        .add_systems(Update, (check_dir_changed, poll_dir_listing).chain())
+        .add_systems(Update, track_directory_history)
+        .add_systems(
+            Update,
+            (check_open_file_changed, switch_model_camera, frame_new_model).chain(),
+        )
+        .add_systems(Update, track_recent_files)
+        .add_systems(Update, clear_selection_on_dir_change)
+        .add_systems(Update, (capture_binding, apply_key_bindings).chain())
+        .add_systems(Update, update_key_contexts)
+        .add_systems(Update, run_palette_actions)
+        .add_systems(Update, apply_fly_mode)
+        .add_systems(Update, load_camera_bookmarks)
+        .add_systems(Update, spin_turntable)
+        .add_systems(Update, (drag_sun, apply_lighting).chain())
+        .add_systems(Update, (load_environment_map, apply_environment_map).chain())
         .add_systems(Update, handle_thumbnail_requests)
         .add_systems(Update, process_thumbnail_queue)
         .add_systems(Update, cleanup_thumbnail_cameras)
+        .add_systems(Last, (remember_camera_on_exit, save_model_stats))
         //observers
         .add_observer(handle_file_nav_up)
         .add_observer(handle_file_nav_down)
+        .add_observer(handle_nav_back)
+        .add_observer(handle_nav_forward)
+        .add_observer(handle_toggle_view)
+        .add_observer(handle_grid_left)
+        .add_observer(handle_grid_right)
+        .add_observer(handle_grid_up)
+        .add_observer(handle_grid_down)
+        .add_observer(handle_grid_page_up)
+        .add_observer(handle_grid_page_down)
+        .add_observer(handle_grid_home)
+        .add_observer(handle_grid_end)
+        .add_observer(handle_grid_open)
+        .add_observer(handle_grid_parent)
+        .add_observer(handle_camera_orbit)
+        .add_observer(handle_camera_pan)
+        .add_observer(handle_camera_zoom_in)
+        .add_observer(handle_camera_zoom_out)
+        .add_observer(handle_view_front)
+        .add_observer(handle_view_back)
+        .add_observer(handle_view_left)
+        .add_observer(handle_view_right)
+        .add_observer(handle_view_top)
+        .add_observer(handle_view_bottom)
+        .add_observer(handle_toggle_projection)
+        .add_observer(handle_toggle_turntable)
+        .add_observer(handle_toggle_shadows)
+        .add_observer(handle_toggle_fly_camera)
+        .add_observer(handle_fly_move)
+        .add_observer(handle_fly_up)
+        .add_observer(handle_fly_down)
+        .add_observer(handle_fly_look)
+        .add_observer(handle_fly_exit)
         .add_observer(check_model_loaded)
         .add_observer(change_material)
          .add_observer(toggle_wireframe)
+        .add_observer(toggle_command_palette)
+        .add_observer(toggle_shortcut_help)
         //input
         .add_input_context::<UiKeyAction>()
+        .add_input_context::<NavKeyAction>()
+        .add_input_context::<GridKeyAction>()
+        .add_input_context::<GamepadCameraAction>()
+        .add_input_context::<ViewKeyAction>()
+        .add_input_context::<FlyKeyAction>()
          .add_input_context::<SystemAction>()
         .run();
 }
 
 
-
-#[derive(Component)]
-pub struct SystemAction;
-
 // Set up the example entities for the 3D scene. The only important thing is a camera which
 // renders directly to the window.
 fn setup_scene(
-    directory: Res<Directory>,
     mut commands: Commands,
     mut egui_global_settings: ResMut<EguiGlobalSettings>,
     _meshes: Res<Assets<Mesh>>,
     _materials: Res<Assets<StandardMaterial>>,
-    sort_mode: Res<SortMode>,
     _asset_server: Res<AssetServer>,
     //mut image_assets: &mut Assets<Image>,
     _image_assets: Res<Assets<Image>>,
 ) {
-    let entries = dir_list_approved_files(&directory.0, *sort_mode);
-
-    commands.insert_resource(FileList(entries));
-
-   
-  commands.spawn((
-        SystemAction,
-        Actions::<SystemAction>::spawn(SpawnWith(|context: &mut ActionSpawner<_>| {
-            // Create the individual key actions that will be part of the chord
-            let alt_key = context
-                .spawn((Action::<AltKey>::new(), bindings![KeyCode::AltLeft]))
-                .id();
-            let r_key = context
-                .spawn((Action::<RKey>::new(),  Press::new(1.0), bindings![KeyCode::KeyR]))
-                .id();
-            
-            // Create the chord action that requires both keys
-            context.spawn((
-                Action::<ToggleWireframe>::new(),               
-                Chord::new([alt_key, r_key]),
-            ));
-        })),
-    ));
-
     // Disable the automatic creation of a primary context to set it up manually for the camera we need.
     egui_global_settings.auto_create_primary_context = false;
 
@@ -146,6 +240,8 @@ fn setup_scene(
         .build(),
         // Ensure this light only affects the main world, not thumbnails
         RenderLayers::layer(0),
+        // Direction, shadows and cascades follow the lighting settings
+        Sun,
     ));
 
     // commands.spawn((
@@ -271,19 +367,6 @@ use bevy::{
 // }
 
 
-// Helper actions for chord members
-#[derive(InputAction)]
-#[action_output(bool)]
-pub struct AltKey;
-
-#[derive(InputAction)]
-#[action_output(bool)]
-pub struct RKey;
-
-#[derive(InputAction)]
-#[action_output(bool)]
-pub struct ToggleWireframe;
-
 pub fn toggle_wireframe(
     _trigger: On<Fire<ToggleWireframe>>,
     mut wireframe_config: ResMut<WireframeConfig>,
@@ -0,0 +1,324 @@
+use bevy::{pbr::wireframe::WireframeConfig, prelude::*};
+use bevy_egui::{EguiContexts, egui};
+use bevy_enhanced_input::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{
+    files::{Directory, FileList, OpenFile},
+    flycam::FlyCamera,
+    gridnav::{GridNav, run_grid_action},
+    history::NavHistory,
+    keybindings::{KeyBindingMenu, KeyBindings, KeyContext, ViewerAction},
+    lighting::Lighting,
+    turntable::Turntable,
+    ui::{OpenCommandPalette, ShowShortcuts, ViewMode, step_open_file},
+    views::run_view_action,
+};
+
+/// Width of the command palette and the shortcut overlay
+const PALETTE_WIDTH: f32 = 420.0;
+
+/// Searchable list of commands opened with Ctrl+P
+#[derive(Resource, Default)]
+pub struct CommandPalette {
+    pub open: bool,
+    query: String,
+    /// Row highlighted among the commands matching `query`
+    selected: usize,
+    /// Set when the highlight moved by keyboard, so the list scrolls to it
+    scroll_to_selected: bool,
+}
+
+/// Overlay listing every shortcut, opened with "?"
+#[derive(Resource, Default)]
+pub struct ShortcutHelp {
+    pub open: bool,
+}
+
+/// Asks for a [`ViewerAction`] to run as if its binding was pressed
+#[derive(Message)]
+pub struct RunAction(pub ViewerAction);
+
+/// Entry of the command palette
+#[derive(Clone, Copy)]
+enum PaletteCommand {
+    Action(ViewerAction),
+    KeyBindings,
+}
+
+impl PaletteCommand {
+    fn label(self) -> &'static str {
+        match self {
+            PaletteCommand::Action(action) => action.label(),
+            PaletteCommand::KeyBindings => "Edit key bindings…",
+        }
+    }
+}
+
+/// Commands that can run in the current view: list and grid actions only while that view is shown
+fn available_commands(grid_active: bool) -> Vec<PaletteCommand> {
+    ViewerAction::ALL
+        .into_iter()
+        // Held while moving the mouse, not a command
+        .filter(|action| !matches!(action, ViewerAction::CommandPalette | ViewerAction::DragSun))
+        .filter(|action| match action.context() {
+            KeyContext::List => !grid_active,
+            KeyContext::Grid => grid_active,
+            KeyContext::Camera => !grid_active,
+            // Held movement keys, not commands
+            KeyContext::Fly => false,
+            _ => true,
+        })
+        .map(PaletteCommand::Action)
+        .chain([PaletteCommand::KeyBindings])
+        .collect()
+}
+
+pub fn toggle_command_palette(
+    _trigger: On<Fire<OpenCommandPalette>>,
+    mut palette: ResMut<CommandPalette>,
+) {
+    palette.open = !palette.open;
+    palette.query.clear();
+    palette.selected = 0;
+}
+
+pub fn toggle_shortcut_help(_trigger: On<Fire<ShowShortcuts>>, mut help: ResMut<ShortcutHelp>) {
+    help.open = !help.open;
+}
+
+/// Runs actions picked from the palette. Their key contexts are suspended while the palette
+/// has focus, so they are applied here rather than through the input observers.
+pub fn run_palette_actions(
+    mut actions: MessageReader<RunAction>,
+    file_list: Res<FileList>,
+    mut open_file: ResMut<OpenFile>,
+    mut directory: ResMut<Directory>,
+    mut history: ResMut<NavHistory>,
+    mut grid_nav: ResMut<GridNav>,
+    mut wireframe_config: ResMut<WireframeConfig>,
+    mut view_mode: ResMut<ViewMode>,
+    mut fly: ResMut<FlyCamera>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+    mut turntable: ResMut<Turntable>,
+    mut lighting: ResMut<Lighting>,
+    mut palette: ResMut<CommandPalette>,
+    mut help: ResMut<ShortcutHelp>,
+) {
+    let (mut camera, mut projection) = camera.into_inner();
+    for RunAction(action) in actions.read() {
+        info!("Running '{}' from the command palette", action.label());
+        match action {
+            ViewerAction::FileNavUp => step_open_file(&file_list, &mut open_file, &directory, -1),
+            ViewerAction::FileNavDown => step_open_file(&file_list, &mut open_file, &directory, 1),
+            ViewerAction::NavBack => {
+                history.go_back(&mut directory, &mut open_file);
+            }
+            ViewerAction::NavForward => {
+                history.go_forward(&mut directory, &mut open_file);
+            }
+            ViewerAction::ToggleWireframe => {
+                wireframe_config.global = !wireframe_config.global;
+            }
+            ViewerAction::ToggleView => *view_mode = view_mode.next(),
+            ViewerAction::ToggleFlyCamera => fly.active = !fly.active,
+            ViewerAction::ViewFront
+            | ViewerAction::ViewBack
+            | ViewerAction::ViewLeft
+            | ViewerAction::ViewRight
+            | ViewerAction::ViewTop
+            | ViewerAction::ViewBottom
+            | ViewerAction::ToggleProjection => {
+                run_view_action(*action, &mut camera, &mut projection)
+            }
+            ViewerAction::ToggleTurntable => turntable.toggle(),
+            ViewerAction::ToggleShadows => {
+                lighting.rig.sun.shadows = !lighting.rig.sun.shadows;
+                lighting.save();
+            }
+            ViewerAction::CommandPalette => palette.open = true,
+            ViewerAction::ShortcutHelp => help.open = true,
+            grid_action => run_grid_action(*grid_action, &mut grid_nav, &mut directory),
+        }
+    }
+}
+
+/// Draws the command palette: a search field over the matching commands with their bindings.
+/// Up/Down pick a command, Enter runs it and Escape closes the palette.
+pub fn command_palette_ui(
+    mut contexts: EguiContexts,
+    mut palette: ResMut<CommandPalette>,
+    mut key_menu: ResMut<KeyBindingMenu>,
+    bindings: Res<KeyBindings>,
+    grid_nav: Res<GridNav>,
+    mut run_actions: MessageWriter<RunAction>,
+) -> Result {
+    if !palette.open {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+
+    let query = palette.query.to_lowercase();
+    let commands: Vec<PaletteCommand> = available_commands(grid_nav.active)
+        .into_iter()
+        .filter(|command| command.label().to_lowercase().contains(&query))
+        .collect();
+
+    // Taken before the search field sees them, which would otherwise drop focus on Enter/Escape
+    let (escape, enter, up, down) = ctx.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+        )
+    });
+    if escape {
+        palette.open = false;
+        return Ok(());
+    }
+    if up || down {
+        let last = commands.len().saturating_sub(1);
+        palette.selected = if up {
+            palette.selected.saturating_sub(1)
+        } else {
+            (palette.selected + 1).min(last)
+        };
+        palette.scroll_to_selected = true;
+    }
+    palette.selected = palette.selected.min(commands.len().saturating_sub(1));
+
+    let mut picked = enter.then(|| commands.get(palette.selected).copied()).flatten();
+
+    egui::Window::new("Command palette")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+        .fixed_size(egui::vec2(PALETTE_WIDTH, 0.0))
+        .show(ctx, |ui| {
+            let search = ui.add(
+                egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("Type a command…")
+                    .desired_width(f32::INFINITY),
+            );
+            search.request_focus();
+            if search.changed() {
+                palette.selected = 0;
+            }
+            ui.separator();
+
+            if commands.is_empty() {
+                ui.weak("No matching command");
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                for (index, command) in commands.iter().enumerate() {
+                    let selected = index == palette.selected;
+                    let binding = match command {
+                        PaletteCommand::Action(action) => bindings.describe(*action),
+                        PaletteCommand::KeyBindings => String::new(),
+                    };
+                    let row = ui
+                        .horizontal(|ui| {
+                            let response = ui.selectable_label(selected, command.label());
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.weak(binding);
+                            });
+                            response
+                        })
+                        .inner;
+                    if row.clicked() {
+                        picked = Some(*command);
+                    }
+                    if selected && palette.scroll_to_selected {
+                        row.scroll_to_me(None);
+                    }
+                }
+            });
+            palette.scroll_to_selected = false;
+        });
+
+    if let Some(command) = picked {
+        palette.open = false;
+        match command {
+            PaletteCommand::Action(action) => {
+                run_actions.write(RunAction(action));
+            }
+            PaletteCommand::KeyBindings => key_menu.open = true,
+        }
+    }
+    Ok(())
+}
+
+/// Draws the cheat sheet of every action and its bindings, grouped by context
+pub fn shortcut_help_ui(
+    mut contexts: EguiContexts,
+    mut help: ResMut<ShortcutHelp>,
+    palette: Res<CommandPalette>,
+    bindings: Res<KeyBindings>,
+) -> Result {
+    if !help.open {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+    if !palette.open && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape))
+    {
+        help.open = false;
+        return Ok(());
+    }
+
+    let mut open = true;
+    egui::Window::new("Keyboard shortcuts")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .default_width(PALETTE_WIDTH)
+        .show(ctx, |ui| {
+            egui::Grid::new("shortcut_help")
+                .num_columns(2)
+                .spacing([24.0, 4.0])
+                .show(ui, |ui| {
+                    let mut context = None;
+                    for action in ViewerAction::ALL {
+                        if context != Some(action.context()) {
+                            context = Some(action.context());
+                            ui.strong(action.context().label());
+                            ui.end_row();
+                        }
+                        let binding = bindings.describe(action);
+                        ui.label(action.label());
+                        if binding.is_empty() {
+                            ui.weak("Unbound");
+                        } else {
+                            ui.monospace(binding);
+                        }
+                        ui.end_row();
+                    }
+
+                    ui.strong("Mouse");
+                    ui.end_row();
+                    for (label, input) in [
+                        ("Orbit camera", "Left drag"),
+                        ("Pan camera", "Right drag"),
+                        ("Zoom", "Wheel"),
+                        ("Add to selection", "Ctrl + Click"),
+                        ("Select range", "Shift + Click"),
+                    ] {
+                        ui.label(label);
+                        ui.monospace(input);
+                        ui.end_row();
+                    }
+                });
+            let palette_keys = bindings.describe(ViewerAction::CommandPalette);
+            if !palette_keys.is_empty() {
+                ui.separator();
+                ui.weak(format!("{palette_keys} runs any of these from the command palette."));
+            }
+        });
+
+    if !open {
+        help.open = false;
+    }
+    Ok(())
+}
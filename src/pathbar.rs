@@ -0,0 +1,218 @@
+use bevy_egui::egui;
+use std::path::{Component, MAIN_SEPARATOR, Path, PathBuf};
+
+/// Maximum number of subdirectory suggestions shown below the path field
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Edit state of the directory path bar, kept between frames
+#[derive(Default)]
+pub struct PathBarState {
+    pub editing: bool,
+    pub text: String,
+    pub error: Option<String>,
+    request_focus: bool,
+    suggestions_for: String,
+    suggestions: Vec<String>,
+}
+
+impl PathBarState {
+    fn start_editing(&mut self, directory: &Path) {
+        self.editing = true;
+        self.text = directory.to_string_lossy().to_string();
+        self.error = None;
+        self.request_focus = true;
+    }
+
+    fn stop_editing(&mut self) {
+        self.editing = false;
+        self.error = None;
+        self.suggestions_for.clear();
+        self.suggestions.clear();
+    }
+}
+
+/// Draws the directory as clickable breadcrumb segments, or as a text field while editing.
+///
+/// Returns the new directory when a segment is clicked or a valid path is committed with Enter.
+pub fn path_bar(ui: &mut egui::Ui, state: &mut PathBarState, directory: &Path) -> Option<PathBuf> {
+    if state.editing {
+        path_edit(ui, state)
+    } else {
+        breadcrumb(ui, state, directory)
+    }
+}
+
+fn breadcrumb(ui: &mut egui::Ui, state: &mut PathBarState, directory: &Path) -> Option<PathBuf> {
+    let mut picked = None;
+
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 2.0;
+
+        let mut segment_path = PathBuf::new();
+        let mut needs_separator = false;
+        for component in directory.components() {
+            segment_path.push(component);
+
+            // Root and drive prefixes already read as a separator
+            let label = match component {
+                Component::RootDir => MAIN_SEPARATOR.to_string(),
+                other => other.as_os_str().to_string_lossy().to_string(),
+            };
+            if needs_separator {
+                ui.label("›");
+            }
+            needs_separator = matches!(
+                component,
+                Component::Normal(_) | Component::CurDir | Component::ParentDir
+            );
+
+            if ui.small_button(label).clicked() {
+                picked = Some(segment_path.clone());
+            }
+        }
+
+        ui.add_space(6.0);
+        if ui.small_button("Edit").on_hover_text("Type a path").clicked() {
+            state.start_editing(directory);
+        }
+    });
+
+    picked.filter(|p| p != directory)
+}
+
+fn path_edit(ui: &mut egui::Ui, state: &mut PathBarState) -> Option<PathBuf> {
+    let mut committed = None;
+    let id = ui.make_persistent_id("path_bar_edit");
+
+    if state.suggestions_for != state.text {
+        state.suggestions = subdirectory_suggestions(&state.text);
+        state.suggestions_for = state.text.clone();
+    }
+
+    // Tab completes to the first suggestion. Consume it before the text edit sees it,
+    // otherwise egui moves keyboard focus to the next widget.
+    let has_focus = ui.memory(|m| m.has_focus(id));
+    if has_focus && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+        if let Some(first) = state.suggestions.first() {
+            state.text = format!("{}{}", first, MAIN_SEPARATOR);
+            move_cursor_to_end(ui.ctx(), id, &state.text);
+        }
+    }
+
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut state.text)
+                .id(id)
+                .hint_text("Directory path")
+                .desired_width(ui.available_width() - 60.0),
+        );
+        if state.request_focus {
+            response.request_focus();
+            state.request_focus = false;
+        }
+        if response.changed() {
+            state.error = None;
+        }
+
+        let (enter, escape) =
+            ui.input(|i| (i.key_pressed(egui::Key::Enter), i.key_pressed(egui::Key::Escape)));
+
+        if response.lost_focus() && enter {
+            match validate_dir(&state.text) {
+                Ok(path) => {
+                    committed = Some(path);
+                    state.stop_editing();
+                }
+                Err(e) => {
+                    state.error = Some(e);
+                    response.request_focus();
+                }
+            }
+        } else if (response.lost_focus() && escape) || ui.button("Cancel").clicked() {
+            state.stop_editing();
+        }
+    });
+
+    if let Some(error) = &state.error {
+        ui.colored_label(egui::Color32::from_rgb(220, 90, 80), error);
+    }
+
+    if state.editing && !state.suggestions.is_empty() {
+        let mut chosen = None;
+        ui.group(|ui| {
+            for suggestion in &state.suggestions {
+                let name = Path::new(suggestion)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| suggestion.clone());
+                if ui.selectable_label(false, name).clicked() {
+                    chosen = Some(suggestion.clone());
+                }
+            }
+        });
+        if let Some(chosen) = chosen {
+            state.text = format!("{}{}", chosen, MAIN_SEPARATOR);
+            state.request_focus = true;
+            move_cursor_to_end(ui.ctx(), id, &state.text);
+        }
+    }
+
+    committed
+}
+
+/// Subdirectories of the typed path's parent whose names start with its last segment
+fn subdirectory_suggestions(text: &str) -> Vec<String> {
+    let path = Path::new(text);
+    let (parent, partial) = if text.ends_with(['/', MAIN_SEPARATOR]) {
+        (path, String::new())
+    } else {
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => (parent, name.to_string_lossy().to_lowercase()),
+            _ => return Vec::new(),
+        }
+    };
+
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+        .filter_map(|e| e.file_name().to_str().map(str::to_string))
+        // Hidden folders are only offered once a dot has been typed
+        .filter(|name| partial.starts_with('.') || !name.starts_with('.'))
+        .filter(|name| name.to_lowercase().starts_with(&partial))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names.truncate(MAX_SUGGESTIONS);
+
+    names
+        .into_iter()
+        .map(|name| parent.join(name).to_string_lossy().to_string())
+        .collect()
+}
+
+fn validate_dir(text: &str) -> Result<PathBuf, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Enter a directory path".to_string());
+    }
+
+    let path = Path::new(text);
+    match std::fs::metadata(path) {
+        Ok(md) if md.is_dir() => Ok(path.to_path_buf()),
+        Ok(_) => Err(format!("Not a directory: {}", path.display())),
+        Err(e) => Err(format!("Cannot open {}: {}", path.display(), e)),
+    }
+}
+
+fn move_cursor_to_end(ctx: &egui::Context, id: egui::Id, text: &str) {
+    if let Some(mut edit_state) = egui::TextEdit::load_state(ctx, id) {
+        let end = egui::text::CCursor::new(text.chars().count());
+        edit_state
+            .cursor
+            .set_char_range(Some(egui::text::CCursorRange::one(end)));
+        edit_state.store(ctx, id);
+    }
+}
@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::{load_json, save_json},
+    files::OpenFile,
+    style::styled_button,
+};
+
+const RECENT_FILES_FILE: &str = "recent_files.json";
+
+/// Number of unpinned entries kept; pinned entries are never dropped
+const MAX_RECENT_FILES: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub last_opened: DateTime<Utc>,
+    pub pinned: bool,
+}
+
+/// Click in [`recent_files_ui`], applied by [`RecentFiles::apply`]
+pub enum RecentClick {
+    Open(PathBuf),
+    TogglePin(PathBuf),
+}
+
+/// Most recently opened models, persisted in the config dir
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct RecentFiles {
+    pub entries: Vec<RecentFile>,
+}
+
+impl RecentFiles {
+    /// Loads the list from disk, dropping files that no longer exist
+    pub fn load() -> Self {
+        let mut recent: RecentFiles = load_json(RECENT_FILES_FILE).unwrap_or_default();
+        if recent.prune_missing() {
+            recent.save();
+        }
+        recent
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = save_json(RECENT_FILES_FILE, self) {
+            warn!("Failed to save recent files: {}", e);
+        }
+    }
+
+    /// Moves `path` to the top of the list, adding it if needed
+    pub fn touch(&mut self, path: &Path) {
+        // JSON can only hold UTF-8 paths; such files are still openable, just not remembered
+        if path.to_str().is_none() {
+            return;
+        }
+        let pinned = self.entries.iter().any(|e| e.path == path && e.pinned);
+        self.entries.retain(|e| e.path != path);
+        self.entries.insert(
+            0,
+            RecentFile {
+                path: path.to_path_buf(),
+                last_opened: Utc::now(),
+                pinned,
+            },
+        );
+
+        // Drop the oldest unpinned entries beyond the limit
+        let mut unpinned = 0;
+        self.entries.retain(|e| {
+            if e.pinned {
+                return true;
+            }
+            unpinned += 1;
+            unpinned <= MAX_RECENT_FILES
+        });
+    }
+
+    pub fn toggle_pin(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.path == path) {
+            entry.pinned = !entry.pinned;
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.retain(|e| e.path != path);
+    }
+
+    /// Removes entries whose files are gone. Returns true if anything was removed.
+    pub fn prune_missing(&mut self) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.path.is_file());
+        self.entries.len() != before
+    }
+
+    pub fn last_opened(&self, path: &Path) -> Option<DateTime<Utc>> {
+        self.entries
+            .iter()
+            .find(|e| e.path == path)
+            .map(|e| e.last_opened)
+    }
+
+    /// Applies a click in [`recent_files_ui`]. Returns the file to open if it still exists.
+    pub fn apply(&mut self, click: RecentClick) -> Option<PathBuf> {
+        match click {
+            RecentClick::TogglePin(path) => {
+                self.toggle_pin(&path);
+                self.save();
+                None
+            }
+            RecentClick::Open(path) if path.is_file() => Some(path),
+            // The file may have been removed since the list was loaded
+            RecentClick::Open(path) => {
+                warn!("Recent file no longer exists: {}", path.display());
+                self.remove(&path);
+                self.save();
+                None
+            }
+        }
+    }
+
+    /// Pinned entries first, then most recently opened first
+    pub fn sorted(&self) -> Vec<&RecentFile> {
+        let mut entries: Vec<&RecentFile> = self.entries.iter().collect();
+        entries.sort_by(|a, b| {
+            b.pinned
+                .cmp(&a.pinned)
+                .then_with(|| b.last_opened.cmp(&a.last_opened))
+        });
+        entries
+    }
+}
+
+/// Adds every newly opened file to the recent list
+pub fn track_recent_files(open_file: Res<OpenFile>, mut recent: ResMut<RecentFiles>) {
+    if !open_file.is_changed() || open_file.is_empty() {
+        return;
+    }
+    recent.touch(&open_file.0);
+    recent.prune_missing();
+    recent.save();
+}
+
+/// Draws the recent files with pin toggles. Only reads the list, so drawing it every frame leaves
+/// the resource unchanged; the caller applies the returned click.
+pub fn recent_files_ui(
+    ui: &mut egui::Ui,
+    recent: &RecentFiles,
+    open_file: &Path,
+) -> Option<RecentClick> {
+    let mut click = None;
+
+    if recent.entries.is_empty() {
+        ui.label("No recent files");
+        return None;
+    }
+
+    for entry in recent.sorted() {
+        let name = entry
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| entry.path.display().to_string());
+
+        ui.horizontal(|ui| {
+            let pin_label = if entry.pinned { "Unpin" } else { "Pin" };
+            if ui.small_button(pin_label).clicked() {
+                click = Some(RecentClick::TogglePin(entry.path.clone()));
+            }
+            let response = styled_button(ui, &name, false, entry.path == open_file, None)
+                .on_hover_text(format!(
+                    "{}\nOpened {}",
+                    entry.path.display(),
+                    entry.last_opened.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                ));
+            if response.clicked() {
+                click = Some(RecentClick::Open(entry.path.clone()));
+            }
+        });
+    }
+
+    click
+}
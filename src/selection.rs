@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::path::{Path, PathBuf};
+
+use crate::files::Directory;
+
+/// Entries picked with Ctrl/Cmd and Shift clicks, shared by the list, grid and details views.
+/// Empty when only the open file is selected.
+#[derive(Resource, Default)]
+pub struct Selection {
+    pub paths: Vec<PathBuf>,
+    /// Entry a Shift+click range starts from
+    anchor: Option<PathBuf>,
+}
+
+impl Selection {
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.iter().any(|p| p == path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.paths.clear();
+        self.anchor = None;
+    }
+
+    /// Applies a click on `path`. Ctrl/Cmd toggles it, Shift selects the range from the last
+    /// clicked entry through `order`, the entries in view order. `current` is the entry selected
+    /// before multi-selection started, usually the open file.
+    ///
+    /// Returns true for a plain click, which the caller handles as opening the entry.
+    pub fn click(
+        &mut self,
+        path: &Path,
+        modifiers: egui::Modifiers,
+        current: Option<&Path>,
+        order: impl FnOnce() -> Vec<PathBuf>,
+    ) -> bool {
+        // Multi-selection starts from what was already highlighted
+        if self.is_empty() && (modifiers.command || modifiers.shift) {
+            if let Some(current) = current {
+                self.paths.push(current.to_path_buf());
+                self.anchor.get_or_insert_with(|| current.to_path_buf());
+            }
+        }
+
+        if modifiers.shift {
+            let anchor = self.anchor.clone().unwrap_or_else(|| path.to_path_buf());
+            let order = order();
+            let position = |p: &Path| order.iter().position(|o| o == p);
+            if let (Some(a), Some(b)) = (position(&anchor), position(path)) {
+                self.paths = order[a.min(b)..=a.max(b)].to_vec();
+            } else {
+                self.paths = vec![path.to_path_buf()];
+                self.anchor = Some(path.to_path_buf());
+            }
+            false
+        } else if modifiers.command {
+            if self.contains(path) {
+                self.paths.retain(|p| p != path);
+            } else {
+                self.paths.push(path.to_path_buf());
+            }
+            self.anchor = Some(path.to_path_buf());
+            false
+        } else {
+            self.clear();
+            self.anchor = Some(path.to_path_buf());
+            true
+        }
+    }
+
+    /// Entries a context menu on `path` applies to: the whole selection if it includes `path`
+    pub fn targets(&self, path: &Path) -> Vec<PathBuf> {
+        if self.contains(path) {
+            self.paths.clone()
+        } else {
+            vec![path.to_path_buf()]
+        }
+    }
+}
+
+/// Drops the selection when another directory is browsed
+pub fn clear_selection_on_dir_change(directory: Res<Directory>, mut selection: ResMut<Selection>) {
+    if directory.is_changed() {
+        selection.clear();
+    }
+}
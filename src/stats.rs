@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::config::{load_json, save_json};
+
+const MODEL_STATS_FILE: &str = "model_stats.json";
+
+/// Seconds between writes while new stats keep coming in
+const SAVE_INTERVAL: f32 = 2.0;
+
+/// Mesh statistics of a model file, gathered whenever it is loaded for viewing or a thumbnail
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ModelStats {
+    pub polygon_count: usize,
+    pub vertex_count: usize,
+    /// Modification time of the file when the stats were taken, in seconds since the epoch
+    pub modified: u64,
+}
+
+/// Stats of every model seen so far, persisted in the config dir so sorting by them
+/// works without loading each file again.
+///
+/// New stats are batched: the resource is only marked changed, and the file only written, by
+/// [`save_model_stats`].
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct ModelStatsCache {
+    pub stats: HashMap<PathBuf, ModelStats>,
+    /// Whether stats were added since the last save
+    #[serde(skip)]
+    dirty: bool,
+    /// Seconds the cache has been dirty
+    #[serde(skip)]
+    dirty_for: f32,
+}
+
+impl ModelStatsCache {
+    /// Loads the cache from disk, dropping files that are gone or changed since
+    pub fn load() -> Self {
+        let mut cache: ModelStatsCache = load_json(MODEL_STATS_FILE).unwrap_or_default();
+        let before = cache.stats.len();
+        cache
+            .stats
+            .retain(|path, stats| modified_secs(path) == Some(stats.modified));
+        if cache.stats.len() != before {
+            cache.save();
+        }
+        cache
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = save_json(MODEL_STATS_FILE, self) {
+            warn!("Failed to save model stats: {}", e);
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&ModelStats> {
+        self.stats.get(path)
+    }
+
+    /// Stores the counts for `path`. Call it through `bypass_change_detection`, so sorting only
+    /// catches up once per batch.
+    pub fn insert(&mut self, path: &Path, polygon_count: usize, vertex_count: usize) {
+        // JSON can only hold UTF-8 paths, and a file without a timestamp can't be validated later
+        let (Some(_), Some(modified)) = (path.to_str(), modified_secs(path)) else {
+            return;
+        };
+        self.stats.insert(
+            path.to_path_buf(),
+            ModelStats {
+                polygon_count,
+                vertex_count,
+                modified,
+            },
+        );
+        self.dirty = true;
+    }
+}
+
+/// Writes new stats to disk and announces them every [`SAVE_INTERVAL`] seconds, and when the
+/// viewer closes
+pub fn save_model_stats(
+    mut cache: ResMut<ModelStatsCache>,
+    time: Res<Time>,
+    mut exits: MessageReader<AppExit>,
+) {
+    let exiting = exits.read().last().is_some();
+    let stats = cache.bypass_change_detection();
+    if !stats.dirty {
+        return;
+    }
+    stats.dirty_for += time.delta_secs();
+    if stats.dirty_for < SAVE_INTERVAL && !exiting {
+        return;
+    }
+    stats.dirty = false;
+    stats.dirty_for = 0.0;
+    stats.save();
+    cache.set_changed();
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
@@ -4,20 +4,36 @@ use bevy::{
     }
 };
 use bevy::asset::LoadState;
-use std::collections::{HashMap, VecDeque};
+use bevy::asset::AssetPath;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+};
 
 use crate::objects::ColorOverride;
 
 /// Resource that stores generated thumbnails for file paths
 #[derive(Resource, Default)]
 pub struct ThumbnailCache {
-    pub thumbnails: HashMap<String, Handle<Image>>,
-    pub pending: HashMap<String, ThumbnailState>,
+    pub thumbnails: HashMap<PathBuf, Handle<Image>>,
+    pub pending: HashMap<PathBuf, ThumbnailState>,
+}
+
+impl ThumbnailCache {
+    /// Forgets a finished thumbnail so the next request renders it again. Returns its image,
+    /// or `None` if there is no thumbnail or it is still being generated.
+    pub fn invalidate(&mut self, path: &Path) -> Option<Handle<Image>> {
+        if !matches!(self.pending.get(path), Some(ThumbnailState::Ready)) {
+            return None;
+        }
+        self.pending.remove(path);
+        self.thumbnails.remove(path)
+    }
 }
 
 #[derive(Resource, Default)]
 pub struct ThumbnailQueue {
-    pub queue: VecDeque<String>,
+    pub queue: VecDeque<PathBuf>,
     pub busy: bool,
 }
 
@@ -33,7 +49,7 @@ pub enum ThumbnailState {
 /// Component marking a thumbnail camera
 #[derive(Component)]
 pub struct ThumbnailCamera {
-    pub file_path: String,
+    pub file_path: PathBuf,
     pub frames_to_render: u32,
     pub layer: u8,
 }
@@ -41,14 +57,14 @@ pub struct ThumbnailCamera {
 /// Component marking a model being rendered for thumbnail
 #[derive(Component)]
 pub struct ThumbnailModel {
-    pub file_path: String,
+    pub file_path: PathBuf,
     pub layer: u8,
 }
 
 //// Component marking a light used for a thumbnail render
 #[derive(Component)]
 pub struct ThumbnailLight {
-    pub file_path: String,
+    pub file_path: PathBuf,
     pub layer: u8,
 }
 
@@ -73,7 +89,7 @@ fn compute_layer(file_path: &str) -> u8 {
 /// Request to generate a thumbnail for a file
 #[derive(Message)]
 pub struct GenerateThumbnail {
-    pub file_path: String,
+    pub file_path: PathBuf,
 }
 
 /// System to handle thumbnail generation requests
@@ -152,7 +168,7 @@ pub fn process_thumbnail_queue(
         let layer = THUMBNAIL_LAYER as u8;
 
         // Prepare scene and rendering resources
-        let scene_path = format!("{}#Scene0", file_path);
+        let scene_path = AssetPath::from_path_buf(file_path.clone()).with_label("Scene0");
         let scene = asset_server.load(scene_path);
         
 
@@ -341,7 +357,7 @@ fn disable_cameras_recursive(entity: Entity, scene_cameras: &mut Query<&mut Came
 
 /// Get or request a thumbnail for a file path
 pub fn get_thumbnail(
-    file_path: &str,
+    file_path: &Path,
     cache: &ThumbnailCache,
     events: &mut MessageWriter<GenerateThumbnail>,
 ) -> Option<Handle<Image>> {
@@ -352,7 +368,7 @@ pub fn get_thumbnail(
         println!("[THUMBNAIL] Requesting thumbnail generation for: {:?}", file_path);
         // Request generation
         events.write(GenerateThumbnail {
-            file_path: file_path.to_string(),
+            file_path: file_path.to_path_buf(),
         });
         None
     }
@@ -0,0 +1,196 @@
+use bevy::{input::mouse::MouseWheel, prelude::*};
+use bevy_egui::egui;
+use bevy_enhanced_input::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{load_json, save_json},
+    files::CurrentGltfEntity,
+    flycam::FlyCamera,
+    ui::ViewMode,
+};
+
+const TURNTABLE_FILE: &str = "turntable.json";
+
+/// Seconds without input before a paused turntable spins again
+const RESUME_AFTER: f32 = 2.0;
+/// Stick deflection that counts as input
+const STICK_THRESHOLD: f32 = 0.2;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleTurntable;
+
+/// What the turntable spins
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum TurntableTarget {
+    /// Circle the camera around its focus; the lights stay put
+    #[default]
+    Camera,
+    /// Rotate the model itself under the camera focus, so the lighting moves across it
+    Model,
+}
+
+/// Slow spin of the 3D view around the vertical axis for reviews, persisted in the config dir
+#[derive(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Turntable {
+    pub enabled: bool,
+    pub target: TurntableTarget,
+    /// Degrees per second
+    pub speed: f32,
+    /// Clockwise as seen from above
+    pub clockwise: bool,
+    /// Seconds since the last user input
+    #[serde(skip)]
+    idle: f32,
+    /// Model root being spun and its transform before the first turn, put back once it stops
+    #[serde(skip)]
+    model_start: Option<(Entity, Transform)>,
+}
+
+impl Default for Turntable {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: TurntableTarget::Camera,
+            speed: 20.0,
+            clockwise: false,
+            idle: RESUME_AFTER,
+            model_start: None,
+        }
+    }
+}
+
+impl Turntable {
+    pub fn load() -> Self {
+        load_json(TURNTABLE_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = save_json(TURNTABLE_FILE, self) {
+            warn!("Failed to save turntable settings: {}", e);
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.save();
+    }
+
+    /// Whether the turntable waits for the user to stop interacting
+    pub fn is_paused(&self) -> bool {
+        self.idle < RESUME_AFTER
+    }
+}
+
+pub fn handle_toggle_turntable(
+    _trigger: On<Fire<ToggleTurntable>>,
+    mut turntable: ResMut<Turntable>,
+) {
+    turntable.toggle();
+}
+
+/// Spins the camera or the model while the turntable is on, pausing while the user presses
+/// keys, buttons or moves a stick. A spun model is put back as it was once the turntable is
+/// switched off or over to the camera.
+pub fn spin_turntable(
+    mut turntable: ResMut<Turntable>,
+    time: Res<Time>,
+    view_mode: Res<ViewMode>,
+    fly: Res<FlyCamera>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut wheel: MessageReader<MouseWheel>,
+    gamepads: Query<&Gamepad>,
+    current_gltf: Res<CurrentGltfEntity>,
+    mut camera: Single<&mut PanOrbitCamera>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let scrolled = wheel.read().count() > 0;
+    let spins_model = turntable.enabled && turntable.target == TurntableTarget::Model;
+    if let Some((root, start)) = turntable.model_start.filter(|_| !spins_model) {
+        turntable.model_start = None;
+        // The model may be gone already, e.g. after opening another file
+        if let Ok(mut transform) = transforms.get_mut(root) {
+            *transform = start;
+        }
+    }
+    if !turntable.enabled || *view_mode != ViewMode::Model || fly.active {
+        return;
+    }
+
+    let gamepad_used = gamepads.iter().any(|gamepad| {
+        gamepad.get_pressed().next().is_some()
+            || gamepad.left_stick().length() > STICK_THRESHOLD
+            || gamepad.right_stick().length() > STICK_THRESHOLD
+    });
+    let input = scrolled
+        || gamepad_used
+        || keys.get_pressed().next().is_some()
+        || mouse_buttons.get_pressed().next().is_some();
+    if input {
+        turntable.idle = 0.0;
+        return;
+    }
+    turntable.idle += time.delta_secs();
+    if turntable.is_paused() {
+        return;
+    }
+
+    let direction = if turntable.clockwise { 1.0 } else { -1.0 };
+    let angle = turntable.speed.to_radians() * direction * time.delta_secs();
+    match turntable.target {
+        // Circling the camera one way makes the model appear to turn the other way
+        TurntableTarget::Camera => camera.target_yaw += angle,
+        TurntableTarget::Model => {
+            let Some(root) = current_gltf.0 else {
+                return;
+            };
+            let Ok(mut transform) = transforms.get_mut(root) else {
+                return;
+            };
+            if turntable.model_start.is_none_or(|(spun, _)| spun != root) {
+                turntable.model_start = Some((root, *transform));
+            }
+            let focus = camera.target_focus;
+            transform.rotate_around(focus, Quat::from_rotation_y(-angle));
+        }
+    }
+}
+
+/// Menu with the turntable switch and settings
+pub fn turntable_menu(ui: &mut egui::Ui, turntable: &mut Turntable, binding: &str) {
+    let title = if turntable.enabled && !turntable.is_paused() {
+        "⟳ Turntable"
+    } else {
+        "Turntable"
+    };
+    ui.menu_button(title, |ui| {
+        let mut changed = false;
+        changed |= ui
+            .checkbox(&mut turntable.enabled, "Spin")
+            .on_hover_text(binding)
+            .changed();
+        ui.add_enabled_ui(turntable.enabled, |ui| {
+            ui.horizontal(|ui| {
+                for (target, label) in [
+                    (TurntableTarget::Camera, "Camera"),
+                    (TurntableTarget::Model, "Model"),
+                ] {
+                    changed |= ui
+                        .radio_value(&mut turntable.target, target, label)
+                        .changed();
+                }
+            });
+            // Saved once a drag ends rather than on every frame of it
+            let speed = ui.add(egui::Slider::new(&mut turntable.speed, 2.0..=90.0).suffix("°/s"));
+            changed |= speed.drag_stopped() || (speed.changed() && !speed.dragged());
+            changed |= ui.checkbox(&mut turntable.clockwise, "Clockwise").changed();
+        });
+        if changed {
+            turntable.save();
+        }
+    });
+}
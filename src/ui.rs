@@ -1,9 +1,23 @@
 use crate::{
     files::{
-        Directory, EditFileName, FileList, ModelInfo, OpenFile,
+        DirListing, Directory, EditFileName, FileEntry, FileList, ModelInfo, OpenFile,
         ShowEditFileName, SortMode,
-        dir_list_approved_files, file_dir_path, open_finder,
+        MODEL_EXTENSIONS, copy_into_dir, file_dir_path, is_model_file, move_into_dir, open_finder,
+        parent_dir,
     },
+    contextmenu::{EntryAction, entry_context_menu},
+    flycam::{FlyCamera, FlyKeyAction, fly_actions},
+    gamepad::{GamepadCameraAction, gamepad_camera_actions},
+    foldertree::{FavoriteFolders, FolderTreeState, folder_tree},
+    gridnav::{GridKeyAction, GridNav, grid_actions},
+    keybindings::{KeyBindingMenu, KeyBindings, ViewerAction},
+    history::NavHistory,
+    pathbar::{PathBarState, path_bar},
+    recent::{RecentFiles, recent_files_ui},
+    selection::Selection,
+    lighting::Lighting,
+    stats::ModelStatsCache,
+    views::{ViewKeyAction, move_sidecar, remove_sidecar, view_actions},
     style::styled_button,
     thumbnails::{GenerateThumbnail, ThumbnailCache, ThumbnailState},
 };
@@ -21,10 +35,36 @@ use bevy_enhanced_input::condition::press::Press;
 use bevy_enhanced_input::{action::Action, actions, prelude::*};
 use bevy_panorbit_camera::PanOrbitCamera;
 use bytesize::ByteSize;
-use std::{fs, path::Path};
+use egui_extras::{Column, TableBuilder};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Height of a row in the file list
+const LIST_ROW_HEIGHT: f32 = 30.0;
+/// Edge length of a thumbnail card in the grid
+const GRID_CARD_SIZE: f32 = 140.0;
+const GRID_SPACING: f32 = 8.0;
+const GRID_LABEL_HEIGHT: f32 = 16.0;
+/// Height of a grid row: card, spacing and file name label
+const GRID_ROW_HEIGHT: f32 = GRID_CARD_SIZE + GRID_SPACING + GRID_LABEL_HEIGHT;
+/// Height of a row in the details table
+const TABLE_ROW_HEIGHT: f32 = 28.0;
+/// Edge length of the thumbnail icon in the details table
+const TABLE_ICON_SIZE: f32 = 24.0;
 
 #[derive(Component)]
 pub struct UiKeyAction;
+
+/// Viewer-wide shortcuts such as the wireframe toggle
+#[derive(Component)]
+pub struct SystemAction;
+
+/// Back/forward navigation, layered above the list and grid contexts so Alt+arrows
+/// take precedence over plain arrows
+#[derive(Component)]
+pub struct NavKeyAction;
 #[derive(InputAction)]
 #[action_output(bool)]
 pub struct FileNavUp;
@@ -33,35 +73,169 @@ pub struct FileNavUp;
 #[action_output(bool)]
 pub struct FileNavDown;
 
-pub fn setup_ui(
-    mut commands: Commands,
-    //mut directory: ResMut<Directory>,
-    //mut egui_global_settings: ResMut<EguiGlobalSettings>,
-    //mut meshes: ResMut<Assets<Mesh>>,
-    //mut materials: ResMut<Assets<StandardMaterial>>,
-    //mut sort_mode: ResMut<SortMode>,
-) {
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct NavBack;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct NavForward;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleWireframe;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleView;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleFlyCamera;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct OpenCommandPalette;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ShowShortcuts;
+
+/// Spawns the input contexts with the actions bound in [`KeyBindings`]
+pub fn setup_ui(mut commands: Commands, bindings: Res<KeyBindings>) {
     commands.spawn((
         UiKeyAction,
-        actions!(UiKeyAction[
-             (
-                Action::<FileNavUp>::new(),
-                Press::new(1.0),
-                bindings![KeyCode::ArrowUp, GamepadButton::LeftTrigger],
-            ),
-             (
-                Action::<FileNavDown>::new(),
-                Press::new(1.0),
-                bindings![KeyCode::ArrowDown, GamepadButton::RightTrigger],
-            )
-        ]),
+        ui_key_actions(&bindings),
+        NavKeyAction,
+        ContextPriority::<NavKeyAction>::new(1),
+        nav_key_actions(&bindings),
+        GridKeyAction,
+        ContextActivity::<GridKeyAction>::INACTIVE,
+        grid_actions(&bindings),
+        gamepad_camera_actions(),
+        ViewKeyAction,
+        view_actions(&bindings),
+        FlyKeyAction,
+        ContextActivity::<FlyKeyAction>::INACTIVE,
+        fly_actions(&bindings),
     ));
+    commands.spawn((SystemAction, system_key_actions(&bindings)));
 }
 
-#[derive(PartialEq)]
+pub fn ui_key_actions(bindings: &KeyBindings) -> impl Bundle {
+    actions!(UiKeyAction[
+        (
+            Action::<FileNavUp>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::FileNavUp),
+        ),
+        (
+            Action::<FileNavDown>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::FileNavDown),
+        ),
+    ])
+}
+
+pub fn nav_key_actions(bindings: &KeyBindings) -> impl Bundle {
+    actions!(NavKeyAction[
+        (
+            Action::<NavBack>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::NavBack),
+        ),
+        (
+            Action::<NavForward>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::NavForward),
+        ),
+    ])
+}
+
+pub fn system_key_actions(bindings: &KeyBindings) -> impl Bundle {
+    actions!(SystemAction[
+        (
+            Action::<ToggleWireframe>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::ToggleWireframe),
+        ),
+        (
+            Action::<ToggleView>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::ToggleView),
+        ),
+        (
+            Action::<ToggleFlyCamera>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::ToggleFlyCamera),
+        ),
+        (
+            Action::<OpenCommandPalette>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::CommandPalette),
+        ),
+        (
+            Action::<ShowShortcuts>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::ShortcutHelp),
+        ),
+    ])
+}
+
+/// Activates the key contexts that fit the current view. While an egui text field has keyboard
+/// focus or a key is being rebound all of them are off, so typing never triggers viewer shortcuts.
+pub fn update_key_contexts(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    grid_nav: Res<GridNav>,
+    view_mode: Res<ViewMode>,
+    fly: Res<FlyCamera>,
+    key_menu: Res<KeyBindingMenu>,
+    ui_keys: Single<Entity, With<UiKeyAction>>,
+    system_keys: Query<Entity, With<SystemAction>>,
+    mut applied: Local<Option<(bool, bool, bool, bool)>>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    let typing = ctx.wants_keyboard_input() || key_menu.is_capturing();
+
+    let model_view = *view_mode == ViewMode::Model;
+    let wanted = (typing, grid_nav.active, model_view, fly.active);
+    if *applied == Some(wanted) {
+        return Ok(());
+    }
+    *applied = Some(wanted);
+
+    commands.entity(*ui_keys).insert((
+        context_activity::<UiKeyAction>(!typing && !grid_nav.active),
+        context_activity::<GridKeyAction>(!typing && grid_nav.active),
+        context_activity::<NavKeyAction>(!typing),
+        context_activity::<GamepadCameraAction>(!typing && model_view && !fly.active),
+        context_activity::<ViewKeyAction>(!typing && model_view && !fly.active),
+        context_activity::<FlyKeyAction>(!typing && fly.active),
+    ));
+    for entity in &system_keys {
+        commands
+            .entity(entity)
+            .insert(context_activity::<SystemAction>(!typing));
+    }
+    Ok(())
+}
+
+fn context_activity<C: Component>(active: bool) -> ContextActivity<C> {
+    if active {
+        ContextActivity::<C>::ACTIVE
+    } else {
+        ContextActivity::<C>::INACTIVE
+    }
+}
+
+/// What the central area shows; switched from the top panel or by [`ViewerAction::ToggleView`]
+#[derive(Resource, PartialEq, Clone, Copy)]
 pub enum ViewMode {
     Model,
     Grid,
+    /// Table of the current directory with sortable columns
+    Details,
 }
 
 impl Default for ViewMode {
@@ -70,15 +244,46 @@ impl Default for ViewMode {
     }
 }
 
+impl ViewMode {
+    /// The view after this one, cycling 3D → Grid → Details
+    pub fn next(self) -> Self {
+        match self {
+            ViewMode::Model => ViewMode::Grid,
+            ViewMode::Grid => ViewMode::Details,
+            ViewMode::Details => ViewMode::Model,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct MyState {
     pub dropped_files: Vec<egui::DroppedFile>,
-    pub picked_path: Option<String>,
-    pub view_mode: ViewMode,
+    /// Result of the last drop or file action, shown in the left panel
+    pub status_message: Option<String>,
+    /// Copy of dropped files running in the background, resolving to its status line
+    pub copy_task: Option<Task<String>>,
+    /// Entries waiting for the user to confirm their deletion
+    pub pending_delete: Vec<PathBuf>,
+    /// Entry being renamed from its context menu, with the new name typed so far
+    pub renaming: Option<(PathBuf, String)>,
+    /// Folder tree row the pointer was last seen over while files are dragged onto the window
+    pub drop_target: Option<PathBuf>,
+    /// Dropped files waiting for the user to confirm moving them into a folder of the tree
+    pub pending_move: Option<(Vec<PathBuf>, PathBuf)>,
+    pub path_bar: PathBarState,
+    pub folder_tree: FolderTreeState,
 }
 
 pub type DialogResponse = Option<rfd::FileHandle>;
 
+/// What a pending native dialog was opened for
+pub enum DialogKind {
+    File,
+    Folder,
+    /// Destination for entries moved from the context menu
+    MoveTo(Vec<PathBuf>),
+}
+
 // then check for keyboard nav stuff
 pub fn handle_file_nav_up(
     _trigger: On<Fire<FileNavUp>>,
@@ -86,20 +291,7 @@ pub fn handle_file_nav_up(
     mut open_file: ResMut<OpenFile>,
     directory: Res<Directory>,
 ) {
-    let path = Path::new(&open_file.0)
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-
-    if let Some(mut index) = file_list.0.iter().position(|x| x.name == path) {
-        if index == 0 {
-            index = file_list.0.len() - 1;
-        } else {
-            index -= 1;
-        }
-        open_file.0 = file_dir_path(directory.0.clone(), file_list.0[index].name.clone());
-    }
+    step_open_file(&file_list, &mut open_file, &directory, -1);
 }
 
 pub fn handle_file_nav_down(
@@ -108,18 +300,205 @@ pub fn handle_file_nav_down(
     mut open_file: ResMut<OpenFile>,
     directory: Res<Directory>,
 ) {
-    let path = Path::new(&open_file.0)
-        .file_name()
+    step_open_file(&file_list, &mut open_file, &directory, 1);
+}
+
+/// Opens the entry `delta` rows away from the open file in the file list, wrapping around
+pub fn step_open_file(
+    file_list: &FileList,
+    open_file: &mut OpenFile,
+    directory: &Directory,
+    delta: isize,
+) {
+    let name = open_file.0.file_name().unwrap_or_default();
+
+    if let Some(index) = file_list.0.iter().position(|x| x.name.as_os_str() == name) {
+        let index = (index as isize + delta).rem_euclid(file_list.0.len() as isize) as usize;
+        open_file.0 = file_dir_path(&directory.0, &file_list.0[index].name);
+    }
+}
+
+pub fn handle_nav_back(
+    _trigger: On<Fire<NavBack>>,
+    mut history: ResMut<NavHistory>,
+    mut directory: ResMut<Directory>,
+    mut open_file: ResMut<OpenFile>,
+) {
+    history.go_back(&mut directory, &mut open_file);
+}
+
+pub fn handle_nav_forward(
+    _trigger: On<Fire<NavForward>>,
+    mut history: ResMut<NavHistory>,
+    mut directory: ResMut<Directory>,
+    mut open_file: ResMut<OpenFile>,
+) {
+    history.go_forward(&mut directory, &mut open_file);
+}
+
+pub fn handle_toggle_view(_trigger: On<Fire<ToggleView>>, mut view_mode: ResMut<ViewMode>) {
+    *view_mode = view_mode.next();
+}
+
+/// Opens `path` and browses its parent directory
+fn open_in_parent(path: PathBuf, directory: &mut Directory, open_file: &mut OpenFile) {
+    if let Some(parent) = path.parent() {
+        if parent != directory.0 {
+            directory.0 = parent.to_path_buf();
+        }
+    }
+    open_file.0 = path;
+}
+
+/// Opens a dropped model or browses a dropped folder. With `copy` the dropped paths are copied
+/// into the current directory instead, in the background. Returns a status line for the UI.
+fn handle_dropped_files(
+    dropped: &[egui::DroppedFile],
+    copy: bool,
+    directory: &mut ResMut<Directory>,
+    open_file: &mut OpenFile,
+    copy_task: &mut Option<Task<String>>,
+) -> String {
+    let paths: Vec<PathBuf> = dropped.iter().filter_map(|f| f.path.clone()).collect();
+    if paths.is_empty() {
+        return "Dropped files have no path on disk".to_string();
+    }
+
+    if copy {
+        if copy_task.is_some() {
+            return "Still copying the last drop, try again once it is done".to_string();
+        }
+        let dir = directory.0.clone();
+        let status = format!("Copying {} item(s) into {}…", paths.len(), dir.display());
+        let task = AsyncComputeTaskPool::get().spawn(async move { copy_paths(&paths, &dir) });
+        *copy_task = Some(task);
+        return status;
+    }
+
+    if let Some(model) = paths.iter().find(|p| p.is_file() && is_model_file(p)) {
+        open_in_parent(model.clone(), directory, open_file);
+        return format!("Opened {}", model.display());
+    }
+    if let Some(folder) = paths.iter().find(|p| p.is_dir()) {
+        directory.0 = folder.clone();
+        return format!("Browsing {}", folder.display());
+    }
+
+    "Unsupported file, drop a .glb/.gltf model or a folder".to_string()
+}
+
+/// Editable text of the last component of `path`
+fn file_name_text(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
+}
+
+/// Copies `paths` into `dir`. Returns a status line for the UI.
+fn copy_paths(paths: &[PathBuf], dir: &Path) -> String {
+    let mut copied = 0;
+    let mut failed = 0;
+    for path in paths {
+        match copy_into_dir(path, dir) {
+            Ok(dest) => {
+                info!("Copied {:?} to {:?}", path, dest);
+                copied += 1;
+            }
+            Err(e) => {
+                error!("{}", e);
+                failed += 1;
+            }
+        }
+    }
+    if failed == 0 {
+        format!("Copied {} item(s) into {}", copied, dir.display())
+    } else {
+        format!("Copied {} item(s), {} failed (see log)", copied, failed)
+    }
+}
+
+/// Moves `paths` into the folder `target`. Returns a status line for the UI.
+fn move_paths(
+    paths: &[PathBuf],
+    target: &Path,
+    directory: &mut ResMut<Directory>,
+    open_file: &mut OpenFile,
+    folder_tree: &mut FolderTreeState,
+) -> String {
+    if paths.is_empty() {
+        return "Nothing to move; dropped files have no path on disk".to_string();
+    }
+
+    let mut moved = 0;
+    let mut failed = 0;
+    for path in paths {
+        match move_into_dir(path, target) {
+            Ok(dest) => {
+                info!("Moved {:?} to {:?}", path, dest);
+                moved += 1;
+                move_sidecar(path, &dest);
+                // Keep showing the open model from its new place
+                if open_file.0 == *path {
+                    open_file.0 = dest;
+                }
+                if let Some(parent) = path.parent() {
+                    folder_tree.invalidate(parent);
+                }
+            }
+            Err(e) => {
+                error!("{}", e);
+                failed += 1;
+            }
+        }
+    }
+    folder_tree.invalidate(target);
+    // The current directory may have lost or gained entries
+    directory.set_changed();
+
+    if failed == 0 {
+        format!("Moved {} item(s) into {}", moved, target.display())
+    } else {
+        format!("Moved {} item(s), {} failed (see log)", moved, failed)
+    }
+}
 
-    if let Some(mut index) = file_list.0.iter().position(|x| x.name == path) {
-        index += 1;
-        if index + 1 > file_list.0.len() {
-            index = 0;
+/// Whether an entry is drawn as selected: part of the [`Selection`], or the open file when nothing
+/// else is selected
+fn is_highlighted(path: &Path, selection: &Selection, open_file: &OpenFile) -> bool {
+    selection.contains(path) || (selection.is_empty() && open_file.0 == path)
+}
+
+/// The open file if it is listed in `dir`, where a multi-selection starts from
+fn open_file_in<'a>(open_file: &'a OpenFile, dir: &Path) -> Option<&'a Path> {
+    (open_file.0.parent() == Some(dir)).then_some(open_file.0.as_path())
+}
+
+/// Egui texture of a ready thumbnail, registered with egui on first use.
+/// Requests generation for readable files that have none yet.
+fn thumbnail_texture(
+    entry: &FileEntry,
+    path: &Path,
+    contexts: &mut EguiContexts,
+    thumbnail_cache: &ThumbnailCache,
+    thumbnail_events: &mut MessageWriter<GenerateThumbnail>,
+) -> Option<egui::TextureId> {
+    // Only display thumbnails that are actually ready; otherwise keep showing placeholder.
+    match (
+        thumbnail_cache.pending.get(path),
+        thumbnail_cache.thumbnails.get(path),
+    ) {
+        (Some(ThumbnailState::Ready), Some(thumbnail_handle)) => {
+            // Egui hands out one id per image; the thumbnail cache owns the image itself
+            Some(contexts.add_image(bevy_egui::EguiTextureHandle::Weak(thumbnail_handle.id())))
+        }
+        // Request thumbnail generation if not in cache, show placeholder
+        (_, None) if entry.error.is_none() => {
+            thumbnail_events.write(GenerateThumbnail {
+                file_path: path.to_path_buf(),
+            });
+            None
         }
-        open_file.0 = file_dir_path(directory.0.clone(), file_list.0[index].name.clone());
+        _ => None,
     }
 }
 
@@ -130,17 +509,28 @@ pub fn ui_system(
     mut directory: ResMut<Directory>,
     mut open_file: ResMut<OpenFile>,
     mut contexts: EguiContexts,
-    _images: Res<Assets<Image>>,
+    mut history: ResMut<NavHistory>,
+    (mut recent_files, mut favorites, mut key_menu, mut lighting): (
+        ResMut<RecentFiles>,
+        ResMut<FavoriteFolders>,
+        ResMut<KeyBindingMenu>,
+        ResMut<Lighting>,
+    ),
     mut camera: Single<&mut Camera, With<PanOrbitCamera>>,
     mut state: Local<MyState>,
-    mut file_dialog: Local<Option<Task<DialogResponse>>>,
+    mut file_dialog: Local<Option<Task<(DialogKind, DialogResponse)>>>,
     window: Single<&mut Window, With<PrimaryWindow>>,
-    mut file_list: ResMut<FileList>,
+    (file_list, dir_listing, mut grid_nav, mut view_mode): (
+        Res<FileList>,
+        Res<DirListing>,
+        ResMut<GridNav>,
+        ResMut<ViewMode>,
+    ),
+    mut selection: ResMut<Selection>,
     mut sort_mode: ResMut<SortMode>,
-    mut show_edit_file_name: ResMut<ShowEditFileName>,
-    mut edit_file_name: ResMut<EditFileName>,
-    model_info: Res<ModelInfo>,
-    thumbnail_cache: Res<ThumbnailCache>,
+    (mut show_edit_file_name, mut edit_file_name): (ResMut<ShowEditFileName>, ResMut<EditFileName>),
+    (model_info, model_stats): (Res<ModelInfo>, Res<ModelStatsCache>),
+    mut thumbnail_cache: ResMut<ThumbnailCache>,
     mut thumbnail_events: MessageWriter<GenerateThumbnail>,
 ) -> Result {
     // Poll the file dialog task FIRST, before any early returns
@@ -148,94 +538,229 @@ pub fn ui_system(
         .as_mut()
         .and_then(|task| block_on(poll_once(task)))
     {
-        state.picked_path = file_response.map(|path| path.path().display().to_string());
+        match file_response {
+            (DialogKind::File, Some(handle)) => {
+                open_in_parent(handle.path().to_path_buf(), &mut directory, &mut open_file);
+            }
+            (DialogKind::Folder, Some(handle)) => {
+                directory.0 = handle.path().to_path_buf();
+            }
+            (DialogKind::MoveTo(paths), Some(handle)) => {
+                state.status_message = Some(move_paths(
+                    &paths,
+                    handle.path(),
+                    &mut directory,
+                    &mut open_file,
+                    &mut state.folder_tree,
+                ));
+                selection.clear();
+            }
+            // Dialog was cancelled
+            (_, None) => {}
+        }
         *file_dialog = None;
     }
 
-    // Pre-fetch texture IDs for all thumbnails BEFORE getting ctx_mut
-    let mut thumbnail_textures: std::collections::HashMap<String, egui::TextureId> =
-        std::collections::HashMap::new();
-    if state.view_mode == ViewMode::Grid {
-        //println!("[UI] Grid mode active, pre-fetching thumbnail textures");
-        //println!("[UI] Total files in list: {}", file_list.0.len());
-        //println!("[UI] Total thumbnails in cache: {}", thumbnail_cache.thumbnails.len());
-
-        for entry in &file_list.0 {
-            let entry_path = std::path::Path::new(&directory.0).join(entry.name.clone());
-            if !entry_path.is_dir() {
-                let entry_path_str = entry_path.to_str().unwrap_or("").to_string();
-                //println!("[UI] Checking thumbnail for: {:?}", entry_path_str);
-
-                // Only display thumbnails that are actually ready; otherwise keep showing placeholder.
-                if let Some(ThumbnailState::Ready) = thumbnail_cache.pending.get(&entry_path_str) {
-                    if let Some(thumbnail_handle) = thumbnail_cache.thumbnails.get(&entry_path_str)
-                    {
-                        // Add Handle<Image> directly to egui and cache the TextureId
-                        let texture_id = contexts.add_image(bevy_egui::EguiTextureHandle::Strong(
-                            thumbnail_handle.clone(),
-                        ));
-                        thumbnail_textures.insert(entry_path_str, texture_id);
-                    }
-                } else {
-                    // Not ready yet; skip adding image id so UI will render the placeholder.
-                }
-            }
-        }
-        //println!("[UI] Pre-fetched {} texture IDs", thumbnail_textures.len());
+    // Re-list the current directory to pick up the copies once they are done
+    if let Some(status) = state
+        .copy_task
+        .as_mut()
+        .and_then(|task| block_on(poll_once(task)))
+    {
+        state.copy_task = None;
+        directory.set_changed();
+        state.status_message = Some(status);
     }
 
-    let ctx = contexts.ctx_mut()?;
+    // Cache which entries are cards in the grid; directories are only shown in the list
+    if file_list.is_changed() {
+        grid_nav.files = file_list
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !entry.is_dir)
+            .map(|(i, _)| i)
+            .collect();
+        // Keep the keyboard focus on the open model when it is listed
+        let open_name = open_file.0.file_name();
+        grid_nav.focus = grid_nav
+            .files
+            .iter()
+            .position(|&i| Some(file_list.0[i].name.as_os_str()) == open_name);
+    }
+
+    // Clone the context handle so `contexts` stays free for registering thumbnails while drawing
+    let ctx = &contexts.ctx_mut()?.clone();
+
+    // Dropping a model opens it, a folder is browsed, holding Alt copies into the current directory.
+    // Dropping onto a folder of the folder tree offers to move the files there instead.
+    let (dropped_files, copy_drop, hovering_files, moved_to) = ctx.input(|i| {
+        (
+            i.raw.dropped_files.clone(),
+            i.modifiers.alt,
+            !i.raw.hovered_files.is_empty(),
+            // Only motion reported during the drag counts: the last known pointer position can
+            // be from before the drag, as the OS often reports none while dragging
+            i.raw.events.iter().rev().find_map(|event| match event {
+                egui::Event::PointerMoved(pos) => Some(*pos),
+                _ => None,
+            }),
+        )
+    });
+    if hovering_files {
+        if let Some(pos) = moved_to {
+            state.drop_target = state.folder_tree.drop_target_at(pos).cloned();
+        }
+    } else if dropped_files.is_empty() {
+        state.drop_target = None;
+    }
+    // The tree refills its targets when drawn; while collapsed it has none
+    state.folder_tree.clear_drop_targets();
+    if !dropped_files.is_empty() {
+        let tree_target = state.drop_target.take();
+        state.status_message = match tree_target {
+            Some(target) => {
+                let paths = dropped_files.iter().filter_map(|f| f.path.clone()).collect();
+                state.pending_move = Some((paths, target));
+                None
+            }
+            None => Some(handle_dropped_files(
+                &dropped_files,
+                copy_drop,
+                &mut directory,
+                &mut open_file,
+                &mut state.copy_task,
+            )),
+        };
+        state.dropped_files = dropped_files;
+    }
+    if hovering_files {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("file_drop_overlay"),
+        ));
+        let rect = ctx.content_rect();
+        painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(160));
+        let hint = match &state.drop_target {
+            Some(target) => format!("Drop to move into {}", target.display()),
+            None => "Drop to open  (hold Alt to copy here)".to_string(),
+        };
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            hint,
+            egui::FontId::proportional(20.0),
+            egui::Color32::WHITE,
+        );
+    }
 
     let my_frame = egui::containers::Frame {
         fill: egui::Color32::from_rgb(15, 16, 17),
         ..Default::default()
     };
 
+    // Picked from an entry's context menu in any of the views, applied once all panels are drawn
+    let mut entry_action = None;
+
     let mut left = egui::SidePanel::left("left_panel")
         .frame(my_frame)
         .resizable(true)
         .show(ctx, |ui| {
-            // text input section
+            // path bar: breadcrumb segments, only commits validated paths
+            if let Some(new_dir) = path_bar(ui, &mut state.path_bar, &directory.0) {
+                directory.0 = new_dir;
+            }
+            ui.label(format!("Open File {}", open_file.0.display()));
+            ui.separator();
+            ui.label(format!("Browsing {}", directory.0.display()));
+
+            ui.label("Drag-and-drop files onto the window!")
+                .on_hover_text("Models open, folders are browsed. Hold Alt to copy into this directory.");
+            if let Some(message) = &state.status_message {
+                ui.label(message);
+            }
+
             ui.horizontal(|ui| {
-                ui.label("Directory:");
-                ui.text_edit_singleline(&mut directory.0);
+                let dialog_open = file_dialog.is_some();
+                if ui
+                    .add_enabled(!dialog_open, egui::Button::new("Open file…"))
+                    .clicked()
+                {
+                    let dialog = rfd::AsyncFileDialog::new()
+                        .set_title("Open model")
+                        .add_filter("3D models", &MODEL_EXTENSIONS)
+                        .set_directory(&directory.0);
+                    *file_dialog = Some(AsyncComputeTaskPool::get().spawn(async move {
+                        (DialogKind::File, dialog.pick_file().await)
+                    }));
+                }
+                if ui
+                    .add_enabled(!dialog_open, egui::Button::new("Open folder…"))
+                    .clicked()
+                {
+                    let dialog = rfd::AsyncFileDialog::new()
+                        .set_title("Browse folder")
+                        .set_directory(&directory.0);
+                    *file_dialog = Some(AsyncComputeTaskPool::get().spawn(async move {
+                        (DialogKind::Folder, dialog.pick_folder().await)
+                    }));
+                }
             });
-            ui.label(format!("Open File {}", open_file.0));
-            ui.separator();
-            ui.label(format!("Browsing {}", directory.0));
 
-            ui.label("Drag-and-drop files onto the window!");
+            egui::CollapsingHeader::new("Recent files")
+                .default_open(false)
+                .show(ui, |ui| {
+                    let click = recent_files_ui(ui, &recent_files, &open_file.0);
+                    if let Some(path) = click.and_then(|click| recent_files.apply(click)) {
+                        open_in_parent(path, &mut directory, &mut open_file);
+                    }
+                });
 
-            if ui.button("Open file…").clicked() {
-                *file_dialog = Some(
-                    AsyncComputeTaskPool::get().spawn(rfd::AsyncFileDialog::new().pick_file()),
-                );
-            }
+            egui::CollapsingHeader::new("Folders")
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_salt("folder_tree_scroll")
+                        .max_height(240.0)
+                        .auto_shrink([false, true])
+                        .show(ui, |ui| {
+                            if let Some(dir) =
+                                folder_tree(ui, &mut state.folder_tree, &mut favorites, &directory.0)
+                            {
+                                directory.0 = dir;
+                            }
+                        });
+                });
 
             ui.separator();
 
             ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(history.can_go_back(), egui::Button::new("Back"))
+                    .on_hover_text("Alt+Left")
+                    .clicked()
+                {
+                    history.go_back(&mut directory, &mut open_file);
+                }
+                if ui
+                    .add_enabled(history.can_go_forward(), egui::Button::new("Forward"))
+                    .on_hover_text("Alt+Right")
+                    .clicked()
+                {
+                    history.go_forward(&mut directory, &mut open_file);
+                }
                 if ui.button("Up").clicked() {
-                    let path = std::fs::canonicalize(&directory.0)
-                        .unwrap_or_else(|_| std::path::PathBuf::from(&directory.0));
-                    if let Some(parent) = path.parent() {
-                        directory.0 = parent.to_string_lossy().to_string();
+                    if let Some(parent) = parent_dir(&directory.0) {
+                        directory.0 = parent;
                     } else {
-                        warn!("Cannot navigate up from directory: {}", directory.0);
+                        warn!("Cannot navigate up from directory: {}", directory.0.display());
                     }
                 }
                 if ui.button("Refresh").clicked() {
-                    file_list.0 = dir_list_approved_files(&directory.0, *sort_mode);
+                    directory.set_changed();
                 }
             });
 
-            if let Some(picked_path) = &state.picked_path {
-                ui.horizontal(|ui| {
-                    ui.label("Picked file:");
-                    ui.monospace(picked_path);
-                });
-            }
-
             // Show dropped files (if any):
             if !state.dropped_files.is_empty() {
                 ui.group(|ui| {
@@ -268,7 +793,7 @@ pub fn ui_system(
 
             ui.separator();
 
-            ui.horizontal(|ui| {
+            ui.horizontal_wrapped(|ui| {
                 if styled_button(ui, "Name", false, *sort_mode == SortMode::Name, None).clicked() {
                     *sort_mode = SortMode::Name;
                 }
@@ -278,6 +803,18 @@ pub fn ui_system(
                 if styled_button(ui, "Date", false, *sort_mode == SortMode::Date, None).clicked() {
                     *sort_mode = SortMode::Date;
                 }
+                if styled_button(ui, "Type", false, *sort_mode == SortMode::Extension, None).clicked() {
+                    *sort_mode = SortMode::Extension;
+                }
+                if styled_button(ui, "Polygons", false, *sort_mode == SortMode::Polygons, None)
+                    .on_hover_text("Files not yet viewed or thumbnailed go last")
+                    .clicked()
+                {
+                    *sort_mode = SortMode::Polygons;
+                }
+                if styled_button(ui, "Opened", false, *sort_mode == SortMode::LastOpened, None).clicked() {
+                    *sort_mode = SortMode::LastOpened;
+                }
                 // if ui.button("Name").clicked() {
                 //     *sort_mode = SortMode::Name;
                 // }
@@ -288,43 +825,77 @@ pub fn ui_system(
                 //     *sort_mode = SortMode::Date;
                 // }
             });
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                for entry in &file_list.0 {
-                    //    ui.label(entry);
-                    // if ui.button(format!("{}", filename)).clicked() {
-                    //     let path = std::path::Path::new(&directory.0).join(filename);
-                    //     if path.is_dir() {
-                    //         directory.0 = path.to_str().unwrap_or(&directory.0).to_string();
-                    //     } else {
-                    //         open_file.0 = path.to_str().unwrap_or("").to_string();
-                    //     }
-                    //     // let md = std::fs::metadata(filename)
-                    //     // if std::fs::
-                    //     // // Handle the button click
-                    //     println!("You clicked: {} ", filename,);
-                    //     // For example, you could trigger opening, previewing, etc.
-                    // }
-                    let path = std::path::Path::new(&directory.0).join(entry.name.clone());
-                    let is_selected = open_file.0 == path.to_str().unwrap_or("").to_string();
+            if let Some(error) = &dir_listing.error {
+                ui.colored_label(egui::Color32::from_rgb(220, 90, 80), error);
+            }
+            if dir_listing.loading {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Loading… {} entries", dir_listing.loaded_count));
+                });
+            }
+            // Offsets restored before the rows exist would be clamped to the top
+            let listed = dir_listing.is_complete(&directory.0);
+            let mut list_scroll = egui::ScrollArea::vertical().id_salt("file_list_scroll");
+            if let Some(offset) = history.pending_list_scroll.take_if(|_| listed) {
+                list_scroll = list_scroll.vertical_scroll_offset(offset);
+            }
+            // Only the rows scrolled into view are laid out
+            let list_output = list_scroll.show_rows(
+                ui,
+                LIST_ROW_HEIGHT,
+                file_list.0.len(),
+                |ui, row_range| {
+                    for entry in &file_list.0[row_range] {
+                        let path = directory.0.join(&entry.name);
+                        let is_selected = is_highlighted(&path, &selection, &open_file);
 
-                    let response = styled_button(
-                        ui,
-                        format!("{}", entry.name).as_ref(),
-                        path.is_dir(),
-                        is_selected,
-                        Some(egui::vec2(200.0, 30.0)),
-                    );
+                        let name = entry.name.to_string_lossy();
+                        let label = match (&entry.error, &entry.link_target) {
+                            (Some(_), _) => format!("⚠ {}", name),
+                            (None, Some(target)) => format!("{} → {}", name, target),
+                            (None, None) => name.to_string(),
+                        };
+                        let mut response = styled_button(
+                            ui,
+                            &label,
+                            entry.is_dir,
+                            is_selected,
+                            Some(egui::vec2(200.0, LIST_ROW_HEIGHT)),
+                        );
+                        if let Some(error) = &entry.error {
+                            response = response.on_hover_text(error);
+                        } else if let Some(target) = &entry.link_target {
+                            response = response.on_hover_text(format!("Link to {}", target));
+                        }
 
-                    // Handle click
-                    if response.clicked() {
-                        if path.is_dir() {
-                            directory.0 = path.to_str().unwrap_or(&directory.0).to_string();
-                        } else {
-                            open_file.0 = path.to_str().unwrap_or("").to_string();
+                        if let Some(action) = entry_context_menu(&response, &path, &selection) {
+                            entry_action = Some(action);
+                        }
+
+                        // Handle click; Ctrl/Cmd and Shift extend the selection instead of opening
+                        if response.clicked() {
+                            let modifiers = ui.input(|i| i.modifiers);
+                            let plain = selection.click(
+                                &path,
+                                modifiers,
+                                open_file_in(&open_file, &directory.0),
+                                || file_list.0.iter().map(|e| directory.0.join(&e.name)).collect(),
+                            );
+                            if plain {
+                                if let Some(error) = &entry.error {
+                                    warn!("Cannot open {}: {}", path.display(), error);
+                                } else if entry.is_dir {
+                                    directory.0 = path;
+                                } else {
+                                    open_file.0 = path;
+                                }
+                            }
                         }
                     }
-                }
-            });
+                },
+            );
+            history.list_scroll = list_output.state.offset.y;
 
             ui.allocate_rect(ui.available_rect_before_wrap(), egui::Sense::hover());
         })
@@ -332,13 +903,6 @@ pub fn ui_system(
         .rect
         .width(); // height is ignored, as the panel has a hight of 100% of the screen
 
-    // Collect dropped files:
-    // ctx.input(|i| {
-    //     if !i.raw.dropped_files.is_empty() {
-    //         state.dropped_files.clone_from(&i.raw.dropped_files);
-    //     }
-    // });
-
     // ctx.input(|i| {
     //     if i.raw.modifiers.ctrl {
     //         info!("ctrl pressed");
@@ -349,9 +913,9 @@ pub fn ui_system(
         .frame(my_frame)
         .resizable(true)
         .show(ctx, |ui| {
-            if open_file.0 != "".to_string() {
+            if !open_file.is_empty() {
                 ui.label("Info");
-                match std::fs::metadata(open_file.0.clone()) {
+                match std::fs::metadata(&open_file.0) {
                     Ok(md) => {
                         let m = format!("Size {:?} bytes", ByteSize(md.len()));
                         ui.label(m);
@@ -360,11 +924,11 @@ pub fn ui_system(
                 }
 
                 if ui.button("Delete File").clicked() {
-                    match fs::remove_file(open_file.0.clone()) {
+                    match fs::remove_file(&open_file.0) {
                         Ok(_) => {
                             println!("Successfully deleted {:?}", open_file.0);
-                            open_file.0 = "".to_string();
-                            file_list.0 = dir_list_approved_files(&directory.0, *sort_mode);
+                            open_file.0 = PathBuf::new();
+                            directory.set_changed();
                         }
                         Err(e) => println!("Error deleting {:?}\n{:?}", open_file.0, e),
                     }
@@ -385,21 +949,27 @@ pub fn ui_system(
         .show(ctx, |ui| {
             // Toggle button for view mode
             ui.horizontal(|ui| {
-                let toggle_label = match state.view_mode {
-                    ViewMode::Model => "Show Grid",
-                    ViewMode::Grid => "Show 3D",
-                };
-                if ui.button(toggle_label).clicked() {
-                    state.view_mode = if state.view_mode == ViewMode::Model {
-                        ViewMode::Grid
-                    } else {
-                        ViewMode::Model
-                    };
+                ui.selectable_value(&mut *view_mode, ViewMode::Model, "3D");
+                ui.selectable_value(&mut *view_mode, ViewMode::Grid, "Grid");
+                ui.selectable_value(&mut *view_mode, ViewMode::Details, "Details");
+                ui.separator();
+                ui.menu_button("Recent", |ui| {
+                    let click = recent_files_ui(ui, &recent_files, &open_file.0);
+                    if let Some(path) = click.and_then(|click| recent_files.apply(click)) {
+                        open_in_parent(path, &mut directory, &mut open_file);
+                        ui.close();
+                    }
+                });
+                if ui.button("Keys").on_hover_text("Key bindings").clicked() {
+                    key_menu.open = !key_menu.open;
+                }
+                if ui.button("Lights").on_hover_text("Sun and shadows").clicked() {
+                    lighting.open = !lighting.open;
                 }
             });
 
             let path = open_file.0.clone(); // std::path::Path::new(&directory.0).join(entry.name.clone());
-            if path != "".to_string() {
+            if !path.as_os_str().is_empty() {
                 ui.horizontal(|ui| {
                     if show_edit_file_name.0 {
                         ui.add_sized(
@@ -408,33 +978,52 @@ pub fn ui_system(
                         );
                         //ui.text_edit_singleline(&mut edit_file_name.0);
                         if ui.button("Save").clicked() {
-                            let src = path.clone();
-                            let dest = edit_file_name.0.clone();
-                            match fs::rename(src, dest.clone()) {
-                                Ok(_) => {
-                                    open_file.0 = dest;
-                                    show_edit_file_name.0 = false;
-                                    file_list.0 = dir_list_approved_files(&directory.0, *sort_mode);
-                                }
-                                Err(e) => {
-                                    //TODO handle this
-                                    println!("{:?}", e);
+                            // Only the name is text, so non-UTF-8 parent folders survive the rename
+                            let name = edit_file_name.0.trim();
+                            let dest = path.with_file_name(name);
+                            if name == file_name_text(&path) {
+                                // Untouched, and possibly a lossy copy of a non-UTF-8 name
+                                show_edit_file_name.0 = false;
+                            } else {
+                                let result = if name.is_empty()
+                                    || name.contains(['/', std::path::MAIN_SEPARATOR])
+                                {
+                                    Err(format!("Invalid name '{}'", name))
+                                } else if dest.exists() {
+                                    Err(format!("{} already exists", dest.display()))
+                                } else {
+                                    fs::rename(&path, &dest)
+                                        .map_err(|e| format!("Failed to rename: {}", e))
+                                };
+                                match result {
+                                    Ok(()) => {
+                                        move_sidecar(&path, &dest);
+                                        state.status_message =
+                                            Some(format!("Renamed to {}", dest.display()));
+                                        open_file.0 = dest;
+                                        show_edit_file_name.0 = false;
+                                        directory.set_changed();
+                                    }
+                                    Err(e) => {
+                                        error!("{}", e);
+                                        state.status_message = Some(e);
+                                    }
                                 }
-                            };
+                            }
                         }
                         if ui.button("Cancel").clicked() {
-                            edit_file_name.0 = path;
+                            edit_file_name.0 = file_name_text(&path);
                             show_edit_file_name.0 = false;
                         }
                     } else {
-                        ui.label(open_file.0.to_string());
+                        ui.label(open_file.0.display().to_string());
                         if ui.button("Rename").clicked() {
-                            edit_file_name.0 = path;
+                            edit_file_name.0 = file_name_text(&path);
                             show_edit_file_name.0 = true;
                         }
 
                         if ui.button( "Find File")                            .clicked()                        {
-                            let res = open_finder(open_file.0.clone());
+                            let res = open_finder(&open_file.0);
 
                         println!("res {:?}",res);
                         }
@@ -473,94 +1062,289 @@ pub fn ui_system(
 
     // Center view area
     // Render grid of 2D cards if in grid mode, otherwise set camera viewport as usual
-    if state.view_mode == ViewMode::Grid {
+    grid_nav.active = *view_mode == ViewMode::Grid;
+    if *view_mode == ViewMode::Grid {
+        let mut opened = None;
+        // Enter on the focused card opens it like a click
+        if std::mem::take(&mut grid_nav.open_focused) {
+            if let Some(index) = grid_nav.focused_entry() {
+                opened = Some(directory.0.join(&file_list.0[index].name));
+            }
+        }
         egui::CentralPanel::default()
             .frame(my_frame)
             .show(ctx, |ui| {
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    //ui.heading("File Grid");
-                    let card_size = egui::vec2(140.0, 140.0);
-                    let spacing = 8.0;
+                let card_size = egui::vec2(GRID_CARD_SIZE, GRID_CARD_SIZE);
+                ui.spacing_mut().item_spacing = egui::vec2(GRID_SPACING, GRID_SPACING);
 
-                    // Compute number of columns based on available width
-                    let available_width = ui.available_width();
-                    let num_columns = ((available_width + spacing) / (card_size.x + spacing))
-                        .floor()
-                        .max(1.0) as usize;
+                // Compute number of columns based on available width
+                let available_width = ui.available_width();
+                let num_columns = ((available_width + GRID_SPACING) / (card_size.x + GRID_SPACING))
+                    .floor()
+                    .max(1.0) as usize;
 
+                let num_rows = grid_nav.files.len().div_ceil(num_columns);
+                grid_nav.columns = num_columns;
+
+                let mut grid_scroll = egui::ScrollArea::vertical().id_salt("file_grid_scroll");
+                if let Some(offset) = history
+                    .pending_grid_scroll
+                    .take_if(|_| dir_listing.is_complete(&directory.0))
+                {
+                    grid_scroll = grid_scroll.vertical_scroll_offset(offset);
+                } else if std::mem::take(&mut grid_nav.scroll_to_focus) {
+                    // Scroll just enough to bring the focused row into view
+                    if let Some(focus) = grid_nav.focus {
+                        let row_span = GRID_ROW_HEIGHT + GRID_SPACING;
+                        let top = (focus / num_columns) as f32 * row_span;
+                        let view_height = grid_nav.visible_rows.max(1) as f32 * row_span;
+                        let offset = history.grid_scroll;
+                        if top < offset {
+                            grid_scroll = grid_scroll.vertical_scroll_offset(top);
+                        } else if top + row_span > offset + view_height {
+                            grid_scroll =
+                                grid_scroll.vertical_scroll_offset(top + row_span - view_height);
+                        }
+                    }
+                }
+                let grid_files = &grid_nav.files;
+                let focus = grid_nav.focus;
+                let mut clicked_focus = None;
+                // Only the rows scrolled into view are laid out, and only their thumbnails requested
+                let grid_output = grid_scroll.show_rows(ui, GRID_ROW_HEIGHT, num_rows, |ui, row_range| {
                     // Make the grid fill the available width
                     ui.set_width(available_width);
-                    egui::Grid::new("file_grid")
-                        .num_columns(num_columns)
-                        .spacing([spacing, spacing])
-                        .show(ui, |ui| {
-                            for (i, entry) in file_list.0.iter().enumerate() {
-                                let entry_path =
-                                    std::path::Path::new(&directory.0).join(entry.name.clone());
-                                if entry_path.is_dir() {
-                                    continue;
-                                }
+                    for row in row_range {
+                        let start = row * num_columns;
+                        let end = (start + num_columns).min(grid_files.len());
+                        ui.horizontal(|ui| {
+                            for (position, &index) in (start..end).zip(&grid_files[start..end]) {
+                                let entry = &file_list.0[index];
+                                let entry_path = directory.0.join(&entry.name);
 
-                                let entry_path_str = entry_path.to_str().unwrap_or("").to_string();
+                                let texture_id = thumbnail_texture(
+                                    entry,
+                                    &entry_path,
+                                    &mut contexts,
+                                    &thumbnail_cache,
+                                    &mut thumbnail_events,
+                                );
 
                                 ui.vertical(|ui| {
-                                    // Try to get thumbnail texture
-                                    if let Some(texture_id) =
-                                        thumbnail_textures.get(&entry_path_str)
-                                    {
-                                        //println!("[UI] Displaying thumbnail for: {:?}", entry_path_str);
-                                        let button = egui::Button::image(egui::Image::new(
-                                            egui::load::SizedTexture::new(*texture_id, card_size),
+                                    ui.set_width(card_size.x);
+                                    let stroke = if focus == Some(position) {
+                                        egui::Stroke::new(2.0, egui::Color32::WHITE)
+                                    } else if is_highlighted(&entry_path, &selection, &open_file) {
+                                        egui::Stroke::new(2.0, ui.visuals().selection.bg_fill)
+                                    } else {
+                                        egui::Stroke::NONE
+                                    };
+                                    let button = match texture_id {
+                                        Some(texture_id) => egui::Button::image(egui::Image::new(
+                                            egui::load::SizedTexture::new(texture_id, card_size),
                                         ))
-                                        .fill(egui::Color32::from_rgb(0, 0, 0))
-                                        .stroke(egui::Stroke::NONE)
-                                        .corner_radius(8);
+                                        .fill(egui::Color32::from_rgb(0, 0, 0)),
+                                        None => egui::Button::image(egui::include_image!(
+                                            "../assets/icons/file.png"
+                                        )),
+                                    }
+                                    .stroke(stroke)
+                                    .corner_radius(egui::CornerRadius::same(8));
 
-                                        if ui.add_sized(card_size, button).clicked() {
-                                            open_file.0 = entry_path_str.clone();
-                                            state.view_mode = ViewMode::Model;
-                                        }
-                                    } else {
-                                        //println!("[UI] Displaying placeholder for: {:?}", entry_path_str);
-                                        // Request thumbnail generation if not in cache, show placeholder
-                                        if !thumbnail_cache.thumbnails.contains_key(&entry_path_str)
-                                        {
-                                            //println!("[UI] Requesting thumbnail generation for: {:?}", entry_path_str);
-                                            thumbnail_events.write(GenerateThumbnail {
-                                                file_path: entry_path_str.clone(),
-                                            });
-                                        } else {
-                                            //println!("[UI] Thumbnail in cache but no texture_id for: {:?}", entry_path_str);
-                                        }
-                                        let button = egui::Button::image(egui::include_image!(
-                                        "../assets/icons/file.png"
-                                    ))
-                                    .corner_radius(egui::CornerRadius::same(8))
-                                    .stroke(egui::Stroke::NONE)
-                                    //.stroke(
-                                      //  egui::Stroke::new(2.0, egui::Color32::from_rgb(0, 0, 0)),
-                                    //)
-                                    ;
-
-                                        if ui.add_sized(card_size, button).clicked() {
-                                            open_file.0 = entry_path_str.clone();
-                                            state.view_mode = ViewMode::Model;
+                                    let response = ui.add_sized(card_size, button);
+                                    if let Some(action) =
+                                        entry_context_menu(&response, &entry_path, &selection)
+                                    {
+                                        entry_action = Some(action);
+                                    }
+                                    if let Some(error) = &entry.error {
+                                        response.on_hover_text(error);
+                                    } else if response.clicked() {
+                                        clicked_focus = Some(position);
+                                        let modifiers = ui.input(|i| i.modifiers);
+                                        let plain = selection.click(
+                                            &entry_path,
+                                            modifiers,
+                                            open_file_in(&open_file, &directory.0),
+                                            || {
+                                                grid_files
+                                                    .iter()
+                                                    .map(|&i| directory.0.join(&file_list.0[i].name))
+                                                    .collect()
+                                            },
+                                        );
+                                        if plain {
+                                            opened = Some(entry_path.clone());
                                         }
                                     }
-                                    //ui.label(&entry.name);
-                                    //ui.add(egui::Label::new(&entry.name).wrap());
                                     ui.add_sized(
-                                        egui::vec2(120.0, 16.0),
-                                        egui::Label::new(&entry.name).truncate(),
+                                        egui::vec2(120.0, GRID_LABEL_HEIGHT),
+                                        egui::Label::new(entry.name.to_string_lossy().to_string())
+                                            .truncate(),
                                     );
                                 });
-                                if (i + 1) % num_columns == 0 {
-                                    ui.end_row();
-                                }
                             }
                         });
+                    }
                 });
+                history.grid_scroll = grid_output.state.offset.y;
+                grid_nav.visible_rows = (grid_output.inner_rect.height()
+                    / (GRID_ROW_HEIGHT + GRID_SPACING))
+                    .floor()
+                    .max(1.0) as usize;
+                if clicked_focus.is_some() {
+                    grid_nav.focus = clicked_focus;
+                }
             });
+
+        if let Some(path) = opened {
+            open_file.0 = path;
+            *view_mode = ViewMode::Model;
+        }
+    } else if *view_mode == ViewMode::Details {
+        // A click selects like the list does, a double click shows the model
+        let mut clicked = None;
+        let mut show_model = false;
+        egui::CentralPanel::default()
+            .frame(my_frame)
+            .show(ctx, |ui| {
+                let icon_size = egui::vec2(TABLE_ICON_SIZE, TABLE_ICON_SIZE);
+
+                TableBuilder::new(ui)
+                    .id_salt("file_table")
+                    .striped(true)
+                    .resizable(true)
+                    .sense(egui::Sense::click())
+                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                    .column(Column::exact(TABLE_ICON_SIZE))
+                    .column(Column::initial(260.0).at_least(80.0).clip(true))
+                    .column(Column::initial(70.0).at_least(40.0))
+                    .column(Column::initial(90.0).at_least(50.0))
+                    .column(Column::initial(140.0).at_least(60.0))
+                    .column(Column::remainder().at_least(60.0))
+                    .header(TABLE_ROW_HEIGHT, |mut header| {
+                        header.col(|_| {});
+                        for (title, mode) in [
+                            ("Name", SortMode::Name),
+                            ("Type", SortMode::Extension),
+                            ("Size", SortMode::Size),
+                            ("Modified", SortMode::Date),
+                            ("Polygons", SortMode::Polygons),
+                        ] {
+                            header.col(|ui| {
+                                let selected = *sort_mode == mode;
+                                let title = if selected {
+                                    format!("{} ⏷", title)
+                                } else {
+                                    title.to_string()
+                                };
+                                if ui.selectable_label(selected, title).clicked() {
+                                    *sort_mode = mode;
+                                }
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        // Rows are virtualized like the list and grid
+                        body.rows(TABLE_ROW_HEIGHT, file_list.0.len(), |mut row| {
+                            let entry = &file_list.0[row.index()];
+                            let path = directory.0.join(&entry.name);
+                            row.set_selected(is_highlighted(&path, &selection, &open_file));
+
+                            row.col(|ui| {
+                                if entry.is_dir {
+                                    ui.label("📁");
+                                    return;
+                                }
+                                let texture_id = thumbnail_texture(
+                                    entry,
+                                    &path,
+                                    &mut contexts,
+                                    &thumbnail_cache,
+                                    &mut thumbnail_events,
+                                );
+                                let image = match texture_id {
+                                    Some(texture_id) => egui::Image::new(
+                                        egui::load::SizedTexture::new(texture_id, icon_size),
+                                    ),
+                                    None => {
+                                        egui::Image::new(egui::include_image!("../assets/icons/file.png"))
+                                    }
+                                };
+                                ui.add(image.fit_to_exact_size(icon_size));
+                            });
+                            row.col(|ui| {
+                                let name = entry.name.to_string_lossy();
+                                let label = match (&entry.error, &entry.link_target) {
+                                    (Some(_), _) => format!("⚠ {}", name),
+                                    (None, Some(target)) => format!("{} → {}", name, target),
+                                    (None, None) => name.to_string(),
+                                };
+                                ui.add(egui::Label::new(label).truncate().selectable(false));
+                            });
+                            row.col(|ui| {
+                                let kind = if entry.is_dir {
+                                    "Folder".to_string()
+                                } else {
+                                    Path::new(&entry.name)
+                                        .extension()
+                                        .map(|ext| ext.to_string_lossy().to_uppercase())
+                                        .unwrap_or_default()
+                                };
+                                ui.label(kind);
+                            });
+                            row.col(|ui| {
+                                if !entry.is_dir && entry.error.is_none() {
+                                    ui.label(ByteSize(entry.size).to_string());
+                                }
+                            });
+                            row.col(|ui| {
+                                if let Some(modified) = entry.modified {
+                                    let modified = chrono::DateTime::<chrono::Local>::from(modified);
+                                    ui.label(modified.format("%Y-%m-%d %H:%M").to_string());
+                                }
+                            });
+                            row.col(|ui| {
+                                if let Some(stats) = model_stats.get(&path) {
+                                    ui.label(stats.polygon_count.to_string());
+                                }
+                            });
+
+                            let response = row.response();
+                            if let Some(action) = entry_context_menu(&response, &path, &selection) {
+                                entry_action = Some(action);
+                            }
+                            if let Some(error) = &entry.error {
+                                response.on_hover_text(error);
+                            } else {
+                                if response.double_clicked() && !entry.is_dir {
+                                    show_model = true;
+                                }
+                                if response.clicked() {
+                                    let modifiers = response.ctx.input(|i| i.modifiers);
+                                    let plain = selection.click(
+                                        &path,
+                                        modifiers,
+                                        open_file_in(&open_file, &directory.0),
+                                        || file_list.0.iter().map(|e| directory.0.join(&e.name)).collect(),
+                                    );
+                                    if plain {
+                                        clicked = Some((path, entry.is_dir));
+                                    }
+                                }
+                            }
+                        });
+                    });
+            });
+
+        match clicked {
+            Some((path, true)) => directory.0 = path,
+            Some((path, false)) => open_file.0 = path,
+            None => {}
+        }
+        if show_model {
+            *view_mode = ViewMode::Model;
+        }
     } else {
         // -------------------------------------------------
         // |  left   |            top   ^^^^^^   |  right  |
@@ -601,5 +1385,212 @@ pub fn ui_system(
         });
     }
 
+    if let Some(action) = entry_action {
+        match action {
+            EntryAction::Open(path) => {
+                selection.clear();
+                if path.is_dir() {
+                    directory.0 = path;
+                } else {
+                    open_file.0 = path;
+                }
+            }
+            EntryAction::Rename(path) => {
+                let name = file_name_text(&path);
+                state.renaming = Some((path, name));
+            }
+            EntryAction::Duplicate(paths) => {
+                let mut duplicated = 0;
+                let mut failed = 0;
+                for path in &paths {
+                    let Some(parent) = path.parent() else {
+                        continue;
+                    };
+                    match copy_into_dir(path, parent) {
+                        Ok(dest) => {
+                            info!("Duplicated {:?} as {:?}", path, dest);
+                            duplicated += 1;
+                        }
+                        Err(e) => {
+                            error!("{}", e);
+                            failed += 1;
+                        }
+                    }
+                }
+                directory.set_changed();
+                state.status_message = Some(if failed == 0 {
+                    format!("Duplicated {} item(s)", duplicated)
+                } else {
+                    format!("Duplicated {} item(s), {} failed (see log)", duplicated, failed)
+                });
+            }
+            EntryAction::Move(paths) => {
+                if file_dialog.is_none() {
+                    let dialog = rfd::AsyncFileDialog::new()
+                        .set_title("Move to folder")
+                        .set_directory(&directory.0);
+                    *file_dialog = Some(AsyncComputeTaskPool::get().spawn(async move {
+                        (DialogKind::MoveTo(paths), dialog.pick_folder().await)
+                    }));
+                }
+            }
+            EntryAction::CopyPath(paths) => {
+                let text: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+                ctx.copy_text(text.join("\n"));
+            }
+            EntryAction::Reveal(path) => {
+                if let Err(e) = open_finder(&path) {
+                    error!("{}", e);
+                }
+            }
+            EntryAction::RegenerateThumbnails(paths) => {
+                for path in paths {
+                    // Thumbnails still being rendered are skipped, they come out fresh anyway
+                    if let Some(handle) = thumbnail_cache.invalidate(&path) {
+                        contexts.remove_image(&handle);
+                        thumbnail_events.write(GenerateThumbnail { file_path: path });
+                    }
+                }
+            }
+            EntryAction::Delete(paths) => state.pending_delete = paths,
+        }
+    }
+
+    if let Some((path, mut name)) = state.renaming.take() {
+        let mut commit = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("rename_modal")).show(ctx, |ui| {
+            ui.label(format!("Rename {}", path.display()));
+            let response = ui.text_edit_singleline(&mut name);
+            response.request_focus();
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                commit = true;
+            }
+            ui.horizontal(|ui| {
+                commit |= ui.button("Rename").clicked();
+                cancel |= ui.button("Cancel").clicked();
+            });
+        });
+        cancel |= modal.should_close();
+
+        if commit {
+            let name = name.trim();
+            let dest = path.with_file_name(name);
+            let result = if name.is_empty() || name.contains(['/', std::path::MAIN_SEPARATOR]) {
+                Err(format!("Invalid name '{}'", name))
+            } else if dest.exists() {
+                Err(format!("{} already exists", dest.display()))
+            } else {
+                fs::rename(&path, &dest).map_err(|e| format!("Failed to rename: {}", e))
+            };
+            match result {
+                Ok(()) => {
+                    move_sidecar(&path, &dest);
+                    if open_file.0 == path {
+                        open_file.0 = dest.clone();
+                    }
+                    selection.clear();
+                    directory.set_changed();
+                    state.status_message = Some(format!("Renamed to {}", dest.display()));
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    state.status_message = Some(e);
+                }
+            }
+        } else if !cancel {
+            state.renaming = Some((path, name));
+        }
+    }
+
+    if let Some((paths, target)) = state.pending_move.take() {
+        let mut confirm = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("move_modal")).show(ctx, |ui| {
+            ui.label(format!("Move {} item(s) into {}?", paths.len(), target.display()));
+            for path in paths.iter().take(5) {
+                ui.label(path.display().to_string());
+            }
+            if paths.len() > 5 {
+                ui.label(format!("… and {} more", paths.len() - 5));
+            }
+            ui.horizontal(|ui| {
+                confirm |= ui.button("Move").clicked();
+                cancel |= ui.button("Cancel").clicked();
+            });
+        });
+        cancel |= modal.should_close();
+
+        if confirm {
+            state.status_message = Some(move_paths(
+                &paths,
+                &target,
+                &mut directory,
+                &mut open_file,
+                &mut state.folder_tree,
+            ));
+        } else if !cancel {
+            state.pending_move = Some((paths, target));
+        }
+    }
+
+    if !state.pending_delete.is_empty() {
+        let paths = std::mem::take(&mut state.pending_delete);
+        let mut confirm = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("delete_modal")).show(ctx, |ui| {
+            ui.label(format!("Delete {} item(s)? This cannot be undone.", paths.len()));
+            for path in paths.iter().take(5) {
+                ui.label(path.display().to_string());
+            }
+            if paths.len() > 5 {
+                ui.label(format!("… and {} more", paths.len() - 5));
+            }
+            ui.horizontal(|ui| {
+                confirm |= ui.button("Delete").clicked();
+                cancel |= ui.button("Cancel").clicked();
+            });
+        });
+        cancel |= modal.should_close();
+
+        if confirm {
+            let mut deleted = 0;
+            let mut failed = 0;
+            for path in &paths {
+                // Links are removed themselves, never what they point to
+                let is_dir = fs::symlink_metadata(path).map(|md| md.is_dir()).unwrap_or(false);
+                let result = if is_dir {
+                    fs::remove_dir_all(path)
+                } else {
+                    fs::remove_file(path)
+                };
+                match result {
+                    Ok(()) => {
+                        info!("Deleted {:?}", path);
+                        remove_sidecar(path);
+                        deleted += 1;
+                        if open_file.0.starts_with(path) {
+                            open_file.0 = PathBuf::new();
+                        }
+                    }
+                    Err(e) => {
+                        error!("Error deleting {:?}: {}", path, e);
+                        failed += 1;
+                    }
+                }
+            }
+            selection.clear();
+            state.folder_tree.invalidate(&directory.0);
+            directory.set_changed();
+            state.status_message = Some(if failed == 0 {
+                format!("Deleted {} item(s)", deleted)
+            } else {
+                format!("Deleted {} item(s), {} failed (see log)", deleted, failed)
+            });
+        } else if !cancel {
+            state.pending_delete = paths;
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,464 @@
+use bevy::{camera::ScalingMode, prelude::*};
+use bevy_egui::{EguiContexts, egui};
+use bevy_enhanced_input::condition::press::Press;
+use bevy_enhanced_input::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    files::OpenFile,
+    flycam::FlyCamera,
+    framing::{ModelCameras, camera_on_open_menu},
+    keybindings::{KeyBindings, ViewerAction},
+    lighting::{DragSun, ToggleShadows},
+    turntable::{ToggleTurntable, Turntable, turntable_menu},
+    ui::ViewMode,
+};
+
+/// Standard views and the projection toggle, active in the 3D view while not flying.
+/// Spawned on the [`crate::ui::UiKeyAction`] entity.
+#[derive(Component)]
+pub struct ViewKeyAction;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ViewFront;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ViewBack;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ViewLeft;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ViewRight;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ViewTop;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ViewBottom;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct ToggleProjection;
+
+pub fn view_actions(bindings: &KeyBindings) -> impl Bundle {
+    // Back, left and bottom default to Ctrl plus the key of the opposite view. They come first
+    // so they consume the key before the plain binding sees it.
+    actions!(ViewKeyAction[
+        (Action::<ViewBack>::new(), Press::new(1.0), bindings.spawn(ViewerAction::ViewBack)),
+        (Action::<ViewLeft>::new(), Press::new(1.0), bindings.spawn(ViewerAction::ViewLeft)),
+        (
+            Action::<ViewBottom>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::ViewBottom),
+        ),
+        (Action::<ViewFront>::new(), Press::new(1.0), bindings.spawn(ViewerAction::ViewFront)),
+        (Action::<ViewRight>::new(), Press::new(1.0), bindings.spawn(ViewerAction::ViewRight)),
+        (Action::<ViewTop>::new(), Press::new(1.0), bindings.spawn(ViewerAction::ViewTop)),
+        (
+            Action::<ToggleProjection>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::ToggleProjection),
+        ),
+        (
+            Action::<ToggleTurntable>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::ToggleTurntable),
+        ),
+        (
+            Action::<ToggleShadows>::new(),
+            Press::new(1.0),
+            bindings.spawn(ViewerAction::ToggleShadows),
+        ),
+        (Action::<DragSun>::new(), bindings.spawn(ViewerAction::DragSun)),
+    ])
+}
+
+/// Orbit yaw and pitch of a standard view; the front looks at the model's +Z side
+fn standard_view(action: ViewerAction) -> Option<(f32, f32)> {
+    match action {
+        ViewerAction::ViewFront => Some((0.0, 0.0)),
+        ViewerAction::ViewBack => Some((PI, 0.0)),
+        ViewerAction::ViewRight => Some((FRAC_PI_2, 0.0)),
+        ViewerAction::ViewLeft => Some((-FRAC_PI_2, 0.0)),
+        ViewerAction::ViewTop => Some((0.0, FRAC_PI_2)),
+        ViewerAction::ViewBottom => Some((0.0, -FRAC_PI_2)),
+        _ => None,
+    }
+}
+
+/// `target` shifted by whole turns to the angle closest to `current`, so snapping never spins
+/// the camera around more than half a turn
+fn nearest_angle(current: f32, target: f32) -> f32 {
+    current + (target - current + PI).rem_euclid(TAU) - PI
+}
+
+/// Runs a standard view or the projection toggle on the viewer camera
+pub fn run_view_action(
+    action: ViewerAction,
+    camera: &mut PanOrbitCamera,
+    projection: &mut Projection,
+) {
+    if let Some((yaw, pitch)) = standard_view(action) {
+        camera.target_yaw = nearest_angle(camera.target_yaw, yaw);
+        camera.target_pitch = pitch;
+    } else if action == ViewerAction::ToggleProjection {
+        set_orthographic(camera, projection, !is_orthographic(projection));
+    }
+}
+
+fn is_orthographic(projection: &Projection) -> bool {
+    matches!(projection, Projection::Orthographic(_))
+}
+
+/// Switches between perspective and orthographic projection. The orthographic view shows the
+/// same height at the focus as the perspective one did, and follows the orbit radius.
+fn set_orthographic(camera: &PanOrbitCamera, projection: &mut Projection, orthographic: bool) {
+    if orthographic == is_orthographic(projection) {
+        return;
+    }
+    *projection = if orthographic {
+        let fov = match projection {
+            Projection::Perspective(p) => p.fov,
+            _ => PerspectiveProjection::default().fov,
+        };
+        Projection::Orthographic(OrthographicProjection {
+            scaling_mode: ScalingMode::FixedVertical {
+                viewport_height: 2.0 * (fov / 2.0).tan(),
+            },
+            scale: camera.target_radius,
+            ..OrthographicProjection::default_3d()
+        })
+    } else {
+        Projection::Perspective(PerspectiveProjection::default())
+    };
+}
+
+fn run_on_camera(action: ViewerAction, camera: Single<(&mut PanOrbitCamera, &mut Projection)>) {
+    let (mut camera, mut projection) = camera.into_inner();
+    run_view_action(action, &mut camera, &mut projection);
+}
+
+pub fn handle_view_front(
+    _trigger: On<Fire<ViewFront>>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    run_on_camera(ViewerAction::ViewFront, camera);
+}
+
+pub fn handle_view_back(
+    _trigger: On<Fire<ViewBack>>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    run_on_camera(ViewerAction::ViewBack, camera);
+}
+
+pub fn handle_view_left(
+    _trigger: On<Fire<ViewLeft>>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    run_on_camera(ViewerAction::ViewLeft, camera);
+}
+
+pub fn handle_view_right(
+    _trigger: On<Fire<ViewRight>>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    run_on_camera(ViewerAction::ViewRight, camera);
+}
+
+pub fn handle_view_top(
+    _trigger: On<Fire<ViewTop>>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    run_on_camera(ViewerAction::ViewTop, camera);
+}
+
+pub fn handle_view_bottom(
+    _trigger: On<Fire<ViewBottom>>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    run_on_camera(ViewerAction::ViewBottom, camera);
+}
+
+pub fn handle_toggle_projection(
+    _trigger: On<Fire<ToggleProjection>>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+) {
+    run_on_camera(ViewerAction::ToggleProjection, camera);
+}
+
+/// Where the orbit camera looks from, and with which projection
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct CameraPose {
+    pub focus: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+    pub radius: f32,
+    #[serde(default)]
+    pub orthographic: bool,
+}
+
+impl CameraPose {
+    pub fn capture(camera: &PanOrbitCamera, projection: &Projection) -> Self {
+        Self {
+            focus: camera.target_focus.to_array(),
+            yaw: camera.target_yaw,
+            pitch: camera.target_pitch,
+            radius: camera.target_radius,
+            orthographic: is_orthographic(projection),
+        }
+    }
+
+    /// Moves the camera targets, so the camera glides to the pose
+    pub fn apply(&self, camera: &mut PanOrbitCamera, projection: &mut Projection) {
+        camera.target_focus = Vec3::from_array(self.focus);
+        camera.target_yaw = nearest_angle(camera.target_yaw, self.yaw);
+        camera.target_pitch = self.pitch;
+        camera.target_radius = self.radius;
+        set_orthographic(camera, projection, self.orthographic);
+    }
+}
+
+/// Named camera pose saved for one model
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CameraBookmark {
+    pub name: String,
+    #[serde(flatten)]
+    pub pose: CameraPose,
+}
+
+/// Bookmarks of the open model, kept in a `<model file>.views.json` sidecar next to it
+#[derive(Resource, Default)]
+pub struct CameraBookmarks {
+    /// Model the bookmarks belong to
+    file: PathBuf,
+    pub bookmarks: Vec<CameraBookmark>,
+    /// Name typed for the next bookmark
+    new_name: String,
+}
+
+impl CameraBookmarks {
+    fn load(file: &Path) -> Self {
+        let path = sidecar_path(file);
+        let bookmarks = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring invalid camera bookmarks '{}': {}", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self {
+            file: file.to_path_buf(),
+            bookmarks,
+            new_name: String::new(),
+        }
+    }
+
+    fn save(&self) {
+        let path = sidecar_path(&self.file);
+        // No empty sidecars are left behind once the last bookmark is deleted
+        let result = if self.bookmarks.is_empty() {
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            }
+        } else {
+            serde_json::to_string_pretty(&self.bookmarks)
+                .map_err(std::io::Error::other)
+                .and_then(|contents| fs::write(&path, contents))
+        };
+        if let Err(e) = result {
+            warn!("Failed to save camera bookmarks '{}': {}", path.display(), e);
+        }
+    }
+}
+
+/// Sidecar holding the camera bookmarks of `model`
+pub fn sidecar_path(model: &Path) -> PathBuf {
+    let mut name = model.as_os_str().to_owned();
+    name.push(".views.json");
+    PathBuf::from(name)
+}
+
+/// Takes the bookmarks of `model` along when it is renamed or moved to `new_model`
+pub fn move_sidecar(model: &Path, new_model: &Path) {
+    let from = sidecar_path(model);
+    if !from.is_file() {
+        return;
+    }
+    let to = sidecar_path(new_model);
+    let result = match fs::rename(&from, &to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            fs::copy(&from, &to).and_then(|_| fs::remove_file(&from))
+        }
+        result => result,
+    };
+    if let Err(e) = result {
+        warn!("Failed to move camera bookmarks '{}': {}", from.display(), e);
+    }
+}
+
+/// Deletes the bookmarks of a deleted `model`
+pub fn remove_sidecar(model: &Path) {
+    let path = sidecar_path(model);
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            warn!("Failed to delete camera bookmarks '{}': {}", path.display(), e);
+        }
+        _ => {}
+    }
+}
+
+/// Reads the bookmarks of each newly opened model
+pub fn load_camera_bookmarks(open_file: Res<OpenFile>, mut bookmarks: ResMut<CameraBookmarks>) {
+    if open_file.is_changed() && bookmarks.file != open_file.0 {
+        *bookmarks = if open_file.0.as_os_str().is_empty() {
+            CameraBookmarks::default()
+        } else {
+            CameraBookmarks::load(&open_file.0)
+        };
+    }
+}
+
+/// Toolbar in the top right of the 3D view with the standard views, the projection toggle, the
+/// turntable, the camera setting for newly opened models and the bookmarks of the open model
+pub fn camera_views_ui(
+    mut contexts: EguiContexts,
+    view_mode: Res<ViewMode>,
+    fly: Res<FlyCamera>,
+    open_file: Res<OpenFile>,
+    bindings: Res<KeyBindings>,
+    camera: Single<(&mut PanOrbitCamera, &mut Projection)>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut model_cameras: ResMut<ModelCameras>,
+    mut turntable: ResMut<Turntable>,
+) -> Result {
+    if *view_mode != ViewMode::Model || fly.active {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+    let (mut camera, mut projection) = camera.into_inner();
+    // The panels are already laid out, so this is the 3D viewport
+    let viewport = ctx.available_rect();
+
+    egui::Area::new(egui::Id::new("camera_views"))
+        .fixed_pos(viewport.right_top() + egui::vec2(-8.0, 8.0))
+        .pivot(egui::Align2::RIGHT_TOP)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (action, label) in [
+                        (ViewerAction::ViewFront, "Front"),
+                        (ViewerAction::ViewBack, "Back"),
+                        (ViewerAction::ViewLeft, "Left"),
+                        (ViewerAction::ViewRight, "Right"),
+                        (ViewerAction::ViewTop, "Top"),
+                        (ViewerAction::ViewBottom, "Bottom"),
+                    ] {
+                        if ui
+                            .small_button(label)
+                            .on_hover_text(bindings.describe(action))
+                            .clicked()
+                        {
+                            run_view_action(action, &mut camera, &mut projection);
+                        }
+                    }
+                    ui.separator();
+                    let label = if is_orthographic(&projection) {
+                        "Ortho"
+                    } else {
+                        "Persp"
+                    };
+                    if ui
+                        .small_button(label)
+                        .on_hover_text(bindings.describe(ViewerAction::ToggleProjection))
+                        .clicked()
+                    {
+                        run_view_action(
+                            ViewerAction::ToggleProjection,
+                            &mut camera,
+                            &mut projection,
+                        );
+                    }
+
+                    ui.separator();
+                    let turntable_keys = bindings.describe(ViewerAction::ToggleTurntable);
+                    turntable_menu(ui, &mut turntable, &turntable_keys);
+                    camera_on_open_menu(ui, &mut model_cameras);
+
+                    if open_file.0.as_os_str().is_empty() {
+                        return;
+                    }
+                    ui.menu_button("Bookmarks", |ui| {
+                        let mut changed = false;
+                        let mut remove = None;
+                        for (index, bookmark) in bookmarks.bookmarks.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.button(&bookmark.name).clicked() {
+                                    bookmark.pose.apply(&mut camera, &mut projection);
+                                    ui.close();
+                                }
+                                if ui.small_button("✖").on_hover_text("Delete bookmark").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                        }
+                        if let Some(index) = remove {
+                            bookmarks.bookmarks.remove(index);
+                            changed = true;
+                        }
+                        if !bookmarks.bookmarks.is_empty() {
+                            ui.separator();
+                        }
+
+                        ui.horizontal(|ui| {
+                            let name_edit = ui.add(
+                                egui::TextEdit::singleline(&mut bookmarks.new_name)
+                                    .hint_text("Bookmark name")
+                                    .desired_width(140.0),
+                            );
+                            let submitted = name_edit.lost_focus()
+                                && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                            let name = bookmarks.new_name.trim().to_string();
+                            let save = ui
+                                .add_enabled(!name.is_empty(), egui::Button::new("Save"))
+                                .clicked();
+                            if (save || submitted) && !name.is_empty() {
+                                let bookmark = CameraBookmark {
+                                    name,
+                                    pose: CameraPose::capture(&camera, &projection),
+                                };
+                                // Saving under an existing name updates that view
+                                let existing =
+                                    bookmarks.bookmarks.iter_mut().find(|b| b.name == bookmark.name);
+                                match existing {
+                                    Some(existing) => *existing = bookmark,
+                                    None => bookmarks.bookmarks.push(bookmark),
+                                }
+                                bookmarks.new_name.clear();
+                                changed = true;
+                            }
+                        });
+
+                        if changed {
+                            bookmarks.save();
+                        }
+                    });
+                });
+            });
+        });
+    Ok(())
+}